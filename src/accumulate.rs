@@ -0,0 +1,340 @@
+use crate::attributes::Packing;
+use crate::cli::Derived;
+use crate::cli::Statistic;
+use crate::cli::Summation;
+use rayon::prelude::*;
+
+// cells per unit of parallel work, about a dozen rows of the global quarter degree grid
+const CHUNK: usize = 1 << 14;
+
+// running sums and observation counts for one window of one upstream variable,
+// laid out flat in the same row-major order as the upstream field minus its time dimension;
+// a cell with a zero count has no mean, and only gets the fill value when it's written out;
+// nobs names the output variable the counts go to, which several accumulators may share;
+// the Welford running mean and squared deviations, and the extremes, are only kept when a statistic needs them;
+// depending on the summation, sums carry a Neumaier compensation term, or are kept as exact integers in
+// upstream's packed units and only scaled once the mean is taken;
+// derived fields are computed from other upstream variables, and directions are averaged as unit vectors,
+// with the cosines in sum and the sines in sine
+
+pub struct Accumulator {
+    pub name: String,
+    pub dims: Vec<(String, usize)>,
+    pub sum: Vec<f64>,
+    pub count: Vec<i32>,
+    pub fill: f64,
+    pub nobs: String,
+    pub statistics: Vec<Statistic>,
+    pub summation: Summation,
+    pub compensation: Vec<f64>,
+    pub packed: Vec<i64>,
+    pub packing: Option<Packing>,
+    pub running: Vec<f64>,
+    pub m2: Vec<f64>,
+    pub min: Vec<f64>,
+    pub max: Vec<f64>,
+    pub derived: Option<Derived>,
+    pub sine: Vec<f64>
+}
+
+impl Accumulator {
+    pub fn new(name: &str, dims: Vec<(String, usize)>, fill: f64, statistics: &[Statistic], summation: Summation) -> Accumulator {
+        let cells = dims.iter().map(|(_, len)| len).product();
+        let spread = statistics.iter().any(|s| *s == Statistic::Variance || *s == Statistic::Std);
+        let extremes = statistics.iter().any(|s| *s == Statistic::Min || *s == Statistic::Max);
+        Accumulator{
+            name: String::from(name),
+            dims,
+            sum: vec![0.0; cells],
+            count: vec![0; cells],
+            fill,
+            nobs: format!("{}_nobs", name),
+            statistics: statistics.to_vec(),
+            summation,
+            compensation: if summation == Summation::Neumaier {vec![0.0; cells]} else {Vec::new()},
+            packed: if summation == Summation::Packed {vec![0; cells]} else {Vec::new()},
+            packing: None,
+            running: if spread {vec![0.0; cells]} else {Vec::new()},
+            m2: if spread {vec![0.0; cells]} else {Vec::new()},
+            min: if extremes {vec![f64::INFINITY; cells]} else {Vec::new()},
+            max: if extremes {vec![f64::NEG_INFINITY; cells]} else {Vec::new()},
+            derived: None,
+            sine: Vec::new()
+        }
+    }
+
+    pub fn cells(&self) -> usize {
+        self.sum.len()
+    }
+
+    pub fn reset(&mut self) {
+        // clear out the previous window so the buffers can be reused
+
+        self.sum.iter_mut().for_each(|s| *s = 0.0);
+        self.count.iter_mut().for_each(|c| *c = 0);
+        self.compensation.iter_mut().for_each(|c| *c = 0.0);
+        self.packed.iter_mut().for_each(|p| *p = 0);
+        self.running.iter_mut().for_each(|m| *m = 0.0);
+        self.m2.iter_mut().for_each(|m| *m = 0.0);
+        self.min.iter_mut().for_each(|m| *m = f64::INFINITY);
+        self.max.iter_mut().for_each(|m| *m = f64::NEG_INFINITY);
+        self.sine.iter_mut().for_each(|s| *s = 0.0);
+        self.packing = None;
+    }
+
+    pub fn circular(&self) -> bool {
+        self.derived == Some(Derived::Direction)
+    }
+
+    pub fn add_direction(&mut self, weight: f64, degrees: f64, i: usize) {
+        // fold weight unit vectors pointing at degrees into cell i, taking them back out for a negative weight
+
+        if self.sine.is_empty() {
+            self.sine = vec![0.0; self.cells()];
+        }
+        let radians = degrees.to_radians();
+        self.sum[i] += weight * radians.cos();
+        self.sine[i] += weight * radians.sin();
+    }
+
+    pub fn add_field(&mut self, packing: &Packing, field: &[f64]) -> Result<(), netcdf::error::Error> {
+        // fold one day's packed field into the window, skipping anything flagged as missing;
+        // cells are independent, so blocks of them are handed out across the worker threads,
+        // and each cell's days are always added in date order, so the result doesn't depend on the threading
+
+        if self.circular() {
+            if self.sine.is_empty() {
+                self.sine = vec![0.0; self.cells()];
+            }
+            self.sum.par_chunks_mut(CHUNK).zip(self.sine.par_chunks_mut(CHUNK)).zip(self.count.par_chunks_mut(CHUNK)).zip(field.par_chunks(CHUNK)).for_each(|(((cosine, sine), count), field)| {
+                for (((x, y), c), packed) in cosine.iter_mut().zip(sine.iter_mut()).zip(count.iter_mut()).zip(field.iter()) {
+                    if let Some(degrees) = packing.unpack(*packed) {
+                        let radians = degrees.to_radians();
+                        *x += radians.cos();
+                        *y += radians.sin();
+                        *c += 1;
+                    }
+                }
+            });
+            return Ok(());
+        }
+
+        match self.summation {
+            Summation::Naive => {
+                self.sum.par_chunks_mut(CHUNK).zip(self.count.par_chunks_mut(CHUNK)).zip(field.par_chunks(CHUNK)).for_each(|((sum, count), field)| {
+                    for ((s, c), packed) in sum.iter_mut().zip(count.iter_mut()).zip(field.iter()) {
+                        if let Some(value) = packing.unpack(*packed) {
+                            *s += value;
+                            *c += 1;
+                        }
+                    }
+                });
+            },
+            Summation::Neumaier => {
+                self.sum.par_chunks_mut(CHUNK).zip(self.compensation.par_chunks_mut(CHUNK)).zip(self.count.par_chunks_mut(CHUNK)).zip(field.par_chunks(CHUNK)).for_each(|(((sum, compensation), count), field)| {
+                    for (((s, k), c), packed) in sum.iter_mut().zip(compensation.iter_mut()).zip(count.iter_mut()).zip(field.iter()) {
+                        if let Some(value) = packing.unpack(*packed) {
+                            // keep the low order bits lost from whichever of the two is smaller
+                            let t = *s + value;
+                            if s.abs() >= value.abs() {
+                                *k += (*s - t) + value;
+                            } else {
+                                *k += (value - t) + *s;
+                            }
+                            *s = t;
+                            *c += 1;
+                        }
+                    }
+                });
+            },
+            Summation::Packed => {
+                // integer sums are exact, but only mean something if every day was packed the same way
+                match &self.packing {
+                    Some(first) if first.scale_factor != packing.scale_factor || first.add_offset != packing.add_offset => {
+                        return Err(format!("'{}' changes its scale_factor or add_offset within a window, so can't be summed in packed units; rerun with --summation neumaier", self.name).into());
+                    },
+                    Some(_) => (),
+                    None => self.packing = Some(packing.clone())
+                }
+                self.packed.par_chunks_mut(CHUNK).zip(self.count.par_chunks_mut(CHUNK)).zip(field.par_chunks(CHUNK)).for_each(|((sum, count), field)| {
+                    for ((s, c), packed) in sum.iter_mut().zip(count.iter_mut()).zip(field.iter()) {
+                        if packing.unpack(*packed).is_some() {
+                            *s += *packed as i64;
+                            *c += 1;
+                        }
+                    }
+                });
+            }
+        }
+
+        // Welford's update, with the counts above already including this day
+        if !self.m2.is_empty() {
+            self.running.par_chunks_mut(CHUNK).zip(self.m2.par_chunks_mut(CHUNK)).zip(self.count.par_chunks(CHUNK)).zip(field.par_chunks(CHUNK)).for_each(|(((running, m2), count), field)| {
+                for (((mean, m), c), packed) in running.iter_mut().zip(m2.iter_mut()).zip(count.iter()).zip(field.iter()) {
+                    if let Some(value) = packing.unpack(*packed) {
+                        let delta = value - *mean;
+                        *mean += delta / (*c as f64);
+                        *m += delta * (value - *mean);
+                    }
+                }
+            });
+        }
+
+        if !self.min.is_empty() {
+            self.min.par_chunks_mut(CHUNK).zip(self.max.par_chunks_mut(CHUNK)).zip(field.par_chunks(CHUNK)).for_each(|((min, max), field)| {
+                for ((lo, hi), packed) in min.iter_mut().zip(max.iter_mut()).zip(field.iter()) {
+                    if let Some(value) = packing.unpack(*packed) {
+                        *lo = lo.min(value);
+                        *hi = hi.max(value);
+                    }
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn means(&self) -> Vec<f64> {
+        // per-cell means, with cells that never saw a real value set to the fill value
+
+        (0..self.cells()).map(|i| {
+            let c = self.count[i];
+            if c == 0 {
+                return self.fill;
+            }
+            if self.circular() {
+                // the direction of the resultant, which is undefined if the vectors cancel out,
+                // up to the rounding of the sines and cosines of whole degrees
+                if self.sum[i].hypot(self.sine[i]) <= 1e-9 * c as f64 {
+                    return self.fill;
+                }
+                return self.sine[i].atan2(self.sum[i]).to_degrees().rem_euclid(360.0);
+            }
+            match (self.summation, &self.packing) {
+                (Summation::Neumaier, _) => (self.sum[i] + self.compensation[i]) / (c as f64),
+                (Summation::Packed, Some(packing)) => (self.packed[i] as f64 / (c as f64)) * packing.scale_factor + packing.add_offset,
+                _ => self.sum[i] / (c as f64)
+            }
+        }).collect()
+    }
+
+    pub fn statistic(&self, statistic: Statistic) -> Vec<f64> {
+        // per-cell sample variance, standard deviation, minimum or maximum; the variance needs
+        // at least two values, and anything without enough is set to the fill value
+
+        (0..self.cells()).map(|i| {
+            let c = self.count[i];
+            match statistic {
+                Statistic::Variance if c > 1 => self.m2[i] / (c - 1) as f64,
+                Statistic::Std if c > 1 => (self.m2[i] / (c - 1) as f64).sqrt(),
+                Statistic::Min if c > 0 => self.min[i],
+                Statistic::Max if c > 0 => self.max[i],
+                _ => self.fill
+            }
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILL: f64 = -2147483647.0;
+
+    fn accumulator(statistics: &[Statistic], summation: Summation) -> Accumulator {
+        Accumulator::new("sla", vec![(String::from("latitude"), 1), (String::from("longitude"), 2)], FILL, statistics, summation)
+    }
+
+    #[test]
+    fn welford_spread() {
+        // a large offset would cost a naive sum of squares most of its digits
+        let days = [1e8 + 4.0, 1e8 + 7.0, 1e8 + 13.0, 1e8 + 16.0];
+        let mut acc = accumulator(&[Statistic::Variance, Statistic::Std, Statistic::Min, Statistic::Max], Summation::Naive);
+        for day in days.iter() {
+            acc.add_field(&Packing::physical(), &[*day, f64::NAN]).unwrap();
+        }
+        assert_eq!(acc.count, vec![4, 0]);
+        assert!((acc.statistic(Statistic::Variance)[0] - 30.0).abs() < 1e-6);
+        assert!((acc.statistic(Statistic::Std)[0] - 30.0_f64.sqrt()).abs() < 1e-6);
+        assert_eq!(acc.statistic(Statistic::Min)[0], 1e8 + 4.0);
+        assert_eq!(acc.statistic(Statistic::Max)[0], 1e8 + 16.0);
+        assert_eq!(acc.statistic(Statistic::Variance)[1], acc.fill);
+        assert_eq!(acc.statistic(Statistic::Min)[1], acc.fill);
+    }
+
+    #[test]
+    fn welford_single_day() {
+        // one value has a minimum and maximum but no sample variance
+        let mut acc = accumulator(&[Statistic::Variance, Statistic::Min], Summation::Naive);
+        acc.add_field(&Packing::physical(), &[2.5, 3.5]).unwrap();
+        assert_eq!(acc.statistic(Statistic::Variance), vec![acc.fill, acc.fill]);
+        assert_eq!(acc.statistic(Statistic::Min), vec![2.5, 3.5]);
+    }
+
+    #[test]
+    fn neumaier_compensates() {
+        // the ones are lost entirely from a plain sum
+        let days = [1.0, 1e100, 1.0, -1e100];
+        let mut naive = accumulator(&[], Summation::Naive);
+        let mut neumaier = accumulator(&[], Summation::Neumaier);
+        for day in days.iter() {
+            naive.add_field(&Packing::physical(), &[*day, 0.1]).unwrap();
+            neumaier.add_field(&Packing::physical(), &[*day, 0.1]).unwrap();
+        }
+        assert_eq!(naive.means()[0], 0.0);
+        assert_eq!(neumaier.means()[0], 0.5);
+        assert!((neumaier.means()[1] - 0.1).abs() < 1e-15);
+    }
+
+    #[test]
+    fn packed_sums() {
+        let packing = Packing{scale_factor: 0.0001, add_offset: 0.0, fill_value: Some(-2147483647.0), missing_value: None, valid_min: None, valid_max: None};
+        let mut acc = accumulator(&[], Summation::Packed);
+        acc.add_field(&packing, &[1234.0, -2147483647.0]).unwrap();
+        acc.add_field(&packing, &[1237.0, -2147483647.0]).unwrap();
+        assert!((acc.means()[0] - 0.12355).abs() < 1e-12);
+        assert_eq!(acc.means()[1], acc.fill);
+
+        // every day of a window has to be packed alike, but the next window can differ
+        let rescaled = Packing{scale_factor: 0.001, ..packing.clone()};
+        assert!(acc.add_field(&rescaled, &[123.0, 0.0]).is_err());
+        acc.reset();
+        acc.add_field(&rescaled, &[123.0, 0.0]).unwrap();
+        assert!((acc.means()[0] - 0.123).abs() < 1e-12);
+    }
+
+    fn directions(days: &[[f64; 2]]) -> Vec<f64> {
+        let mut acc = accumulator(&[], Summation::Neumaier);
+        acc.derived = Some(Derived::Direction);
+        for day in days.iter() {
+            acc.add_field(&Packing::physical(), day).unwrap();
+        }
+        acc.means()
+    }
+
+    fn bearing(a: f64, b: f64) -> f64 {
+        // the angle between two directions in degrees
+        let d = (a - b).rem_euclid(360.0);
+        d.min(360.0 - d)
+    }
+
+    #[test]
+    fn circular_mean() {
+        // across north, not through south
+        let means = directions(&[[350.0, 80.0], [10.0, 100.0]]);
+        assert!(bearing(means[0], 0.0) < 1e-9, "{}", means[0]);
+        assert!(bearing(means[1], 90.0) < 1e-9, "{}", means[1]);
+        let means = directions(&[[170.0, 0.0], [190.0, f64::NAN], [180.0, f64::NAN]]);
+        assert!(bearing(means[0], 180.0) < 1e-9, "{}", means[0]);
+        assert_eq!(means[1], 0.0);
+    }
+
+    #[test]
+    fn circular_cancelling() {
+        // opposite currents have no mean direction, nor does a cell without any
+        let means = directions(&[[90.0, f64::NAN], [270.0, f64::NAN]]);
+        assert_eq!(means, vec![FILL, FILL]);
+        let means = directions(&[[0.0, 45.0], [120.0, 165.0], [240.0, 285.0]]);
+        assert_eq!(means, vec![FILL, FILL]);
+    }
+}
@@ -0,0 +1,160 @@
+use netcdf::attribute::AttrValue;
+
+// impementing a foreign trait on a forein struct //////////
+// per the advice in https://stackoverflow.com/questions/76277096/deconstructing-enums-in-rust/76277117#76277117
+
+pub struct Wrapper{
+    pub s: String
+}
+
+impl std::convert::TryFrom<AttrValue> for Wrapper {
+    type Error = &'static str;
+
+    fn try_from(value: AttrValue) -> Result<Self, Self::Error> {
+
+        if let AttrValue::Str(v) = value {
+            Ok(Wrapper{s: v} )
+        } else {
+            Err("nope")
+        }
+    }
+}
+
+// numeric attributes come in whatever type the producer chose; widen them all to f64
+pub struct Number{
+    pub v: f64
+}
+
+impl std::convert::TryFrom<AttrValue> for Number {
+    type Error = &'static str;
+
+    fn try_from(value: AttrValue) -> Result<Self, Self::Error> {
+
+        let v = match value {
+            AttrValue::Uchar(x) => x as f64,
+            AttrValue::Schar(x) => x as f64,
+            AttrValue::Ushort(x) => x as f64,
+            AttrValue::Short(x) => x as f64,
+            AttrValue::Uint(x) => x as f64,
+            AttrValue::Int(x) => x as f64,
+            AttrValue::Ulonglong(x) => x as f64,
+            AttrValue::Longlong(x) => x as f64,
+            AttrValue::Float(x) => x as f64,
+            AttrValue::Double(x) => x,
+            // single-element arrays turn up from some writers
+            AttrValue::Uchars(x) if x.len() == 1 => x[0] as f64,
+            AttrValue::Schars(x) if x.len() == 1 => x[0] as f64,
+            AttrValue::Ushorts(x) if x.len() == 1 => x[0] as f64,
+            AttrValue::Shorts(x) if x.len() == 1 => x[0] as f64,
+            AttrValue::Uints(x) if x.len() == 1 => x[0] as f64,
+            AttrValue::Ints(x) if x.len() == 1 => x[0] as f64,
+            AttrValue::Ulonglongs(x) if x.len() == 1 => x[0] as f64,
+            AttrValue::Longlongs(x) if x.len() == 1 => x[0] as f64,
+            AttrValue::Floats(x) if x.len() == 1 => x[0] as f64,
+            AttrValue::Doubles(x) if x.len() == 1 => x[0],
+            _ => return Err("not a single number")
+        };
+        Ok(Number{v})
+    }
+}
+////////////////////
+
+pub fn text(attr: Option<netcdf::Attribute>) -> Option<String> {
+    // the value of a string attribute, if there is one and it is a string
+
+    attr.and_then(|a| a.value().ok()).and_then(|v| Wrapper::try_from(v).ok()).map(|w| w.s)
+}
+
+fn number(var: &netcdf::Variable, name: &str) -> Result<Option<f64>, netcdf::error::Error> {
+    // a numeric attribute of var, if it has one
+
+    match var.attribute(name) {
+        Some(attr) => {
+            let n = Number::try_from(attr.value()?).map_err(|e| format!("attribute '{}' of '{}': {}", name, var.name(), e))?;
+            Ok(Some(n.v))
+        },
+        None => Ok(None)
+    }
+}
+
+// CF packing and masking attributes of an upstream variable
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packing {
+    pub scale_factor: f64,
+    pub add_offset: f64,
+    pub fill_value: Option<f64>,
+    pub missing_value: Option<f64>,
+    pub valid_min: Option<f64>,
+    pub valid_max: Option<f64>
+}
+
+impl Packing {
+    pub fn from_variable(var: &netcdf::Variable) -> Result<Packing, netcdf::error::Error> {
+        // read the packing attributes, defaulting to an unpacked variable with no fill
+
+        Ok(Packing{
+            scale_factor: number(var, "scale_factor")?.unwrap_or(1.0),
+            add_offset: number(var, "add_offset")?.unwrap_or(0.0),
+            fill_value: number(var, "_FillValue")?,
+            missing_value: number(var, "missing_value")?,
+            valid_min: number(var, "valid_min")?,
+            valid_max: number(var, "valid_max")?
+        })
+    }
+
+    pub fn physical() -> Packing {
+        // values already in physical units, with NaN the only missing value
+
+        Packing{scale_factor: 1.0, add_offset: 0.0, fill_value: None, missing_value: None, valid_min: None, valid_max: None}
+    }
+
+    pub fn unpack(&self, packed: f64) -> Option<f64> {
+        // physical value of a packed one, or None if it's flagged as missing or falls outside the valid range;
+        // CF puts the fill, missing and valid values in packed units, so they're checked before scaling
+
+        if packed.is_nan() || Some(packed) == self.fill_value || Some(packed) == self.missing_value {
+            return None;
+        }
+        if self.valid_min.is_some_and(|min| packed < min) || self.valid_max.is_some_and(|max| packed > max) {
+            return None;
+        }
+
+        Some(packed * self.scale_factor + self.add_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sla() -> Packing {
+        // as upstream packs sla: int32 in units of 0.1 mm
+        Packing{scale_factor: 0.0001, add_offset: 0.0, fill_value: Some(-2147483647.0), missing_value: None, valid_min: Some(-100000.0), valid_max: Some(100000.0)}
+    }
+
+    #[test]
+    fn unpack_scales() {
+        let packing = Packing{add_offset: 1.5, ..sla()};
+        assert!((packing.unpack(1234.0).unwrap() - 1.6234).abs() < 1e-12);
+        assert_eq!(Packing::physical().unpack(-3.25), Some(-3.25));
+    }
+
+    #[test]
+    fn unpack_masks() {
+        let packing = Packing{missing_value: Some(-99999.0), ..sla()};
+        assert_eq!(packing.unpack(-2147483647.0), None);
+        assert_eq!(packing.unpack(-99999.0), None);
+        assert_eq!(packing.unpack(f64::NAN), None);
+        assert_eq!(Packing::physical().unpack(f64::NAN), None);
+    }
+
+    #[test]
+    fn unpack_valid_range() {
+        // the valid range is in packed units and inclusive
+        let packing = sla();
+        assert_eq!(packing.unpack(100000.0), Some(10.0));
+        assert_eq!(packing.unpack(-100000.0), Some(-10.0));
+        assert_eq!(packing.unpack(100001.0), None);
+        assert_eq!(packing.unpack(-100001.0), None);
+    }
+}
@@ -0,0 +1,55 @@
+use std::io::Read;
+use std::path::Path;
+
+// CRC-32 as used by zlib, gzip and zip, so a recorded checksum can be reproduced with
+// e.g. python -c "import zlib,sys; print('%08x' % zlib.crc32(open(sys.argv[1],'rb').read()))" file.nc
+
+const POLYNOMIAL: u32 = 0xedb88320;
+
+fn table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = if c & 1 == 1 {POLYNOMIAL ^ (c >> 1)} else {c >> 1};
+        }
+        *entry = c;
+    }
+    table
+}
+
+pub fn crc32(path: &Path) -> Result<String, netcdf::error::Error> {
+    // checksum of a whole file, as crc32:<eight hex digits>
+
+    let table = table();
+    let mut file = std::fs::File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut buf = vec![0u8; 1 << 20];
+    let mut crc = 0xffffffffu32;
+    loop {
+        let n = file.read(&mut buf).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        for byte in buf[..n].iter() {
+            crc = table[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+    }
+
+    Ok(format!("crc32:{:08x}", crc ^ 0xffffffff))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value() {
+        // the standard check value of CRC-32, as zlib.crc32(b"123456789") gives it
+        let path = std::env::temp_dir().join(format!("ssh-crc32-{}", std::process::id()));
+        std::fs::write(&path, "123456789").unwrap();
+        assert_eq!(crc32(&path).unwrap(), "crc32:cbf43926");
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(crc32(&path).unwrap(), "crc32:00000000");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
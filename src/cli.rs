@@ -0,0 +1,439 @@
+use chrono::NaiveDate;
+use clap::Args;
+use clap::CommandFactory;
+use clap::Parser;
+use clap::error::ErrorKind;
+use clap::Subcommand;
+use clap::ValueEnum;
+use std::path::PathBuf;
+
+use crate::inputs;
+use crate::lattice;
+
+#[derive(Parser)]
+#[command(version, about = "Weekly averages of the Copernicus daily sea level products")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Average the daily upstream files onto the weekly lattice, one output file per year
+    Average(RunArgs),
+    /// Print the windows, daily inputs and output files a run would use, without reading any data
+    Inspect(RunArgs),
+    /// Check that every daily input a run needs exists and carries the requested variables
+    Validate(RunArgs),
+    /// Build a monthly or day-of-year climatology over reference years, and optionally average the selected years as departures from it
+    Climatology(ClimatologyArgs),
+    /// Fit a linear, and optionally a quadratic, trend to every cell of the averaged record, written as maps in mm/yr
+    Trend(TrendArgs),
+    /// Take area weighted global and hemispheric means of every window of the averaged record, written as NetCDF and CSV time series
+    Gmsl(GmslArgs)
+}
+
+impl Cli {
+    pub fn checked() -> Cli {
+        // parse the command line, then check the options clap can't check one at a time
+
+        let cli = Cli::parse();
+        let run = match &cli.command {
+            Command::Average(run) | Command::Inspect(run) | Command::Validate(run) => Some(run),
+            Command::Climatology(args) => Some(&args.run),
+            Command::Trend(_) | Command::Gmsl(_) => None
+        };
+        if let Some(Err(message)) = run.map(RunArgs::check) {
+            Cli::command().error(ErrorKind::ArgumentConflict, message).exit();
+        }
+        cli
+    }
+}
+
+#[derive(Args)]
+pub struct GmslArgs {
+    /// Calendar year or inclusive range of years of averaged files to take means of; defaults to every year with an averaged file in the input directory
+    #[arg(long, value_parser = parse_years)]
+    pub year: Option<(i32, i32)>,
+
+    /// Directory holding the averaged files written by average or climatology --anomalies
+    #[arg(long, default_value = "data")]
+    pub input_dir: PathBuf,
+
+    /// Directory the time series are written to
+    #[arg(long, default_value = "data")]
+    pub output_dir: PathBuf,
+
+    #[command(flatten)]
+    pub averaged: AveragedArgs,
+
+    /// Comma-separated averaged variables to take means of
+    #[arg(long, value_delimiter = ',', default_value = "sla,adt")]
+    pub variables: Vec<String>,
+
+    /// Add each window's mean tpa_correction to the sla and adt means, correcting the TOPEX-A drift of 1993-1998, unless the averages were already corrected with average --tpa-correction
+    #[arg(long)]
+    pub tpa_correction: bool
+}
+
+#[derive(Args)]
+pub struct TrendArgs {
+    /// Calendar year or inclusive range of years of averaged files to fit, e.g. 1993-2021; defaults to every year with an averaged file in the input directory
+    #[arg(long, value_parser = parse_years)]
+    pub year: Option<(i32, i32)>,
+
+    /// Directory holding the averaged files written by average or climatology --anomalies
+    #[arg(long, default_value = "data")]
+    pub input_dir: PathBuf,
+
+    /// Directory the trend maps are written to
+    #[arg(long, default_value = "data")]
+    pub output_dir: PathBuf,
+
+    #[command(flatten)]
+    pub averaged: AveragedArgs,
+
+    /// Comma-separated averaged variables to fit; each has to be a length, like sla or adt
+    #[arg(long, value_delimiter = ',', default_value = "sla")]
+    pub variables: Vec<String>,
+
+    /// Also fit a parabola to every cell, and write its acceleration in mm/yr^2
+    #[arg(long)]
+    pub quadratic: bool,
+
+    /// Cells with fewer window means than this are written as fill
+    #[arg(long, default_value_t = 52, value_parser = clap::value_parser!(i64).range(4..))]
+    pub min_windows: i64,
+
+    /// Deflate level for the maps, from 0 (uncompressed) to 9
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(i32).range(0..=9))]
+    pub deflate: i32,
+
+    /// Number of worker threads; each window's field is folded into the fits in parallel
+    #[arg(long, default_value_t = default_jobs(), value_parser = parse_jobs)]
+    pub jobs: usize
+}
+
+#[derive(Args)]
+pub struct AveragedArgs {
+    /// Period of the averaged files to read
+    #[arg(long, value_enum, default_value_t = Period::Days)]
+    pub period: Period,
+
+    /// Window length in days of the averaged files to read, with --period days
+    #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(i64).range(1..))]
+    pub window: i64,
+
+    /// Days between window labels of the averaged files to read, with --period days; defaults to the window length
+    #[arg(long, value_parser = clap::value_parser!(i64).range(1..))]
+    pub stride: Option<i64>,
+
+    /// Window alignment of the averaged files to read
+    #[arg(long, value_enum, default_value_t = Alignment::Forward)]
+    pub align: Alignment,
+
+    /// Read the anomaly files instead of the means
+    #[arg(long)]
+    pub anomalies: bool
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum ClimatologyKind {
+    /// one mean per calendar month
+    Monthly,
+    /// one mean per day of the year, smoothed over --smoothing days
+    DayOfYear
+}
+
+#[derive(Args)]
+pub struct ClimatologyArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Inclusive range of years the climatology is built from, e.g. 1993-2012
+    #[arg(long, value_parser = parse_years, default_value = "1993-2012")]
+    pub reference: (i32, i32),
+
+    /// Whether to build a monthly or a day-of-year climatology
+    #[arg(long, value_enum, default_value_t = ClimatologyKind::Monthly)]
+    pub climatology: ClimatologyKind,
+
+    /// Width in days of the running mean that smooths a day-of-year climatology; even widths are rounded up.
+    /// A smoothed climatology keeps only its means, so --statistics then applies to the departures alone
+    #[arg(long, default_value_t = 31, value_parser = clap::value_parser!(i64).range(1..=365))]
+    pub smoothing: i64,
+
+    /// After building the climatology, average the years selected by --year and friends as departures from it
+    #[arg(long)]
+    pub anomalies: bool
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Derived {
+    /// eddy kinetic energy, half the sum of the squared velocity anomalies ugosa and vgosa
+    Eke,
+    /// speed of the absolute velocity ugos, vgos
+    Speed,
+    /// direction the absolute velocity ugos, vgos flows towards, in degrees clockwise from north, averaged as unit vectors
+    Direction
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Summation {
+    /// plain running sums
+    Naive,
+    /// running sums with Neumaier's compensation for lost low order bits
+    Neumaier,
+    /// exact integer sums of upstream's packed values, scaled once per mean; variables not packed upstream use neumaier
+    Packed
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Statistic {
+    /// sample variance of the daily values
+    Variance,
+    /// sample standard deviation of the daily values
+    Std,
+    /// smallest daily value
+    Min,
+    /// largest daily value
+    Max
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Period {
+    /// windows of --window days, labelled every --stride days
+    Days,
+    /// calendar months
+    Month,
+    /// calendar years
+    Year
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Alignment {
+    /// the window starts on its label date
+    Forward,
+    /// the window is centered on its label date
+    Centered,
+    /// the window ends on its label date
+    Backward
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum MissingPolicy {
+    /// stop before reading any data
+    Fail,
+    /// pass over absent days and average the ones that are there
+    Skip
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum FillValue {
+    /// -999.9, as the weekly record has always used
+    Sentinel,
+    /// IEEE NaN
+    Nan,
+    /// the packed _FillValue (or missing_value) of each upstream variable
+    Upstream
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Chunking {
+    /// one window of the whole grid per chunk, for reading maps
+    Map,
+    /// every window of a 32x32 tile per chunk, for reading time series
+    Series
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Precision {
+    F64,
+    F32,
+    /// int32 with the upstream scale_factor and add_offset, for variables packed upstream; others are written as f32
+    Packed
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// Calendar year or inclusive range of years to process, e.g. 1993 or 1993-1998
+    #[arg(long, value_parser = parse_years)]
+    pub year: Option<(i32, i32)>,
+
+    /// Drop windows that start before this date (yyyy-mm-dd)
+    #[arg(long, value_parser = parse_date)]
+    pub start: Option<NaiveDate>,
+
+    /// Drop windows that end after this date (yyyy-mm-dd); defaults to the last day of the DT2021 record
+    #[arg(long, value_parser = parse_date, default_value = lattice::RECORD_END)]
+    pub end: NaiveDate,
+
+    /// Directory holding the daily upstream files
+    #[arg(long, default_value = "data")]
+    pub input_dir: PathBuf,
+
+    /// Daily upstream filename, with {date} standing for yyyymmdd, {version} for the product version and, with --discover, * for any text
+    #[arg(long, default_value = inputs::DEFAULT_TEMPLATE)]
+    pub input_template: String,
+
+    /// Product version substituted for {version}, e.g. DT2021 or DT2024; defaults to DT2021, or to any version with --discover
+    #[arg(long)]
+    pub product_version: Option<String>,
+
+    /// Scan the input directory for files matching the template instead of building each filename from it
+    #[arg(long)]
+    pub discover: bool,
+
+    /// Directory the averaged files are written to
+    #[arg(long, default_value = "data")]
+    pub output_dir: PathBuf,
+
+    /// What each window covers: a number of days, or a calendar month or year
+    #[arg(long, value_enum, default_value_t = Period::Days)]
+    pub period: Period,
+
+    /// Number of days averaged into each window with --period days
+    #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(i64).range(1..))]
+    pub window: i64,
+
+    /// Days between window labels with --period days, stepping from 1993-01-03; defaults to the window length, e.g. --window 7 --stride 1 for a daily running mean
+    #[arg(long, value_parser = clap::value_parser!(i64).range(1..))]
+    pub stride: Option<i64>,
+
+    /// Where each window sits relative to the date it's labelled with in the output; calendar periods are labelled by their first, middle or last day
+    #[arg(long, value_enum, default_value_t = Alignment::Forward)]
+    pub align: Alignment,
+
+    /// What to do about daily inputs that aren't there: fail before doing any work, or skip them and average the rest
+    #[arg(long, value_enum, default_value_t = MissingPolicy::Fail)]
+    pub missing: MissingPolicy,
+
+    /// With --missing skip, windows with fewer daily inputs than this are written as fill instead of averaged
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(i64).range(1..))]
+    pub min_days: i64,
+
+    /// Value written for cells with no observations in a window, and declared as each mean's _FillValue
+    #[arg(long, value_enum, default_value_t = FillValue::Sentinel)]
+    pub fill_value: FillValue,
+
+    /// Deflate level for the means and counts, from 0 (uncompressed) to 9
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(i32).range(0..=9))]
+    pub deflate: i32,
+
+    /// Shuffle bytes before deflating, which usually compresses floating point fields much better
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    pub shuffle: bool,
+
+    /// Chunk shape of the means and counts
+    #[arg(long, value_enum, default_value_t = Chunking::Map)]
+    pub chunking: Chunking,
+
+    /// Storage type of the means
+    #[arg(long, value_enum, default_value_t = Precision::F32)]
+    pub precision: Precision,
+
+    /// Write one nobs count shared by every variable on the same dimensions as the first, instead of one per variable; the run stops if their masks ever differ
+    #[arg(long)]
+    pub shared_nobs: bool,
+
+    /// Record a CRC-32 of every daily input next to its name in the output, at the cost of reading each file twice
+    #[arg(long)]
+    pub checksums: bool,
+
+    /// Skip years whose output is already complete, and pick up partially written years where they left off
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Number of worker threads; years are processed concurrently and each day's field is accumulated in parallel
+    #[arg(long, default_value_t = default_jobs(), value_parser = parse_jobs)]
+    pub jobs: usize,
+
+    /// Add each day's tpa_correction to the daily sla and adt fields before averaging, correcting the TOPEX-A drift of 1993-1998
+    #[arg(long)]
+    pub tpa_correction: bool,
+
+    /// Comma-separated upstream variables to average; any daily field works, e.g. err_sla or flag_ice
+    #[arg(long, value_delimiter = ',', default_value = "sla,adt,ugosa,vgosa,ugos,vgos,tpa_correction")]
+    pub variables: Vec<String>,
+
+    /// Comma-separated fields to compute from each day's geostrophic velocities and average alongside the variables, each with its own count
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub derived: Vec<Derived>,
+
+    /// How the daily values of each window are summed for the mean
+    #[arg(long, value_enum, default_value_t = Summation::Neumaier)]
+    pub summation: Summation,
+
+    /// Comma-separated statistics to write per cell and window alongside each mean, as <variable>_<statistic>
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub statistics: Vec<Statistic>
+}
+
+impl RunArgs {
+    pub fn check(&self) -> Result<(), String> {
+        // a window can't have more days in it than it's long
+
+        let longest = match self.period {
+            Period::Days => self.window,
+            Period::Month => 31,
+            Period::Year => 366
+        };
+        if self.min_days > longest {
+            return Err(format!("--min-days {} is more than the {} days a window can have", self.min_days, longest));
+        }
+        Ok(())
+    }
+}
+
+fn default_jobs() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+fn parse_jobs(arg: &str) -> Result<usize, String> {
+    match arg.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("'{}' is not a positive number of workers", arg))
+    }
+}
+
+fn parse_date(arg: &str) -> Result<NaiveDate, String> {
+    lattice::parse_date(arg).map_err(|e| e.to_string())
+}
+
+fn parse_years(arg: &str) -> Result<(i32, i32), String> {
+    // accept either a single year or a first-last range
+
+    let bad = || format!("'{}' is not a year or a range of years like 1993-1998", arg);
+    let (first, last) = match arg.split_once('-') {
+        Some((first, last)) => (first, last),
+        None => (arg, arg)
+    };
+    let first = first.trim().parse::<i32>().map_err(|_| bad())?;
+    let last = last.trim().parse::<i32>().map_err(|_| bad())?;
+    if first > last {
+        return Err(bad());
+    }
+
+    Ok((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> RunArgs {
+        match Cli::try_parse_from([&["ssh", "average"], args].concat()).map(|cli| cli.command) {
+            Ok(Command::Average(run)) => run,
+            _ => panic!("could not parse {:?}", args)
+        }
+    }
+
+    #[test]
+    fn min_days_within_window() {
+        assert!(run(&["--missing", "skip", "--min-days", "7"]).check().is_ok());
+        assert!(run(&["--missing", "skip", "--min-days", "8"]).check().is_err());
+        assert!(run(&["--window", "10", "--min-days", "8"]).check().is_ok());
+        assert!(run(&["--period", "month", "--min-days", "28"]).check().is_ok());
+        assert!(run(&["--period", "month", "--min-days", "32"]).check().is_err());
+    }
+}
@@ -0,0 +1,407 @@
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::NaiveDate;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::accumulate::Accumulator;
+use crate::attributes::Number;
+use crate::attributes::Packing;
+use crate::attributes;
+use crate::cli::ClimatologyKind;
+use crate::cli::Summation;
+use crate::grid::Grid;
+use crate::lattice;
+use crate::output;
+use crate::output::Provenance;
+use crate::output::Storage;
+
+// climatological times are labelled in a leap year, so the 29th of February has a place too
+const LABEL_YEAR: i32 = 2000;
+
+// cell_methods of a climatological mean, which tells them apart from other statistics in the file
+const CELL_METHODS: &str = "time: mean within years time: mean over years";
+
+// a per-cell climatology file, and what it was built from;
+// its time axis holds one slot per calendar month or day of the year
+
+pub struct Climatology {
+    pub path: PathBuf,
+    pub kind: ClimatologyKind,
+    pub reference: (i32, i32),
+    pub smoothing: i64
+}
+
+pub fn slots(kind: ClimatologyKind) -> usize {
+    match kind {
+        ClimatologyKind::Monthly => 12,
+        ClimatologyKind::DayOfYear => 366
+    }
+}
+
+pub fn slot(kind: ClimatologyKind, date: NaiveDate) -> usize {
+    // which slot of the climatology a date belongs to
+
+    match kind {
+        ClimatologyKind::Monthly => date.month0() as usize,
+        ClimatologyKind::DayOfYear => NaiveDate::from_ymd_opt(LABEL_YEAR, date.month(), date.day()).expect("every month and day exists in a leap year").ordinal0() as usize
+    }
+}
+
+fn label(kind: ClimatologyKind, slot: usize) -> NaiveDate {
+    // representative date of a slot: the middle of the month, or the day itself
+
+    let first = NaiveDate::from_ymd_opt(LABEL_YEAR, 1, 1).expect("the first of January is always a valid date");
+    match kind {
+        ClimatologyKind::Monthly => {
+            let start = NaiveDate::from_ymd_opt(LABEL_YEAR, slot as u32 + 1, 1).expect("slots are months");
+            let next = if slot == 11 {NaiveDate::from_ymd_opt(LABEL_YEAR + 1, 1, 1)} else {NaiveDate::from_ymd_opt(LABEL_YEAR, slot as u32 + 2, 1)}.expect("slots are months");
+            start + Duration::days((next.signed_duration_since(start).num_days() - 1) / 2)
+        },
+        ClimatologyKind::DayOfYear => first + Duration::days(slot as i64)
+    }
+}
+
+pub fn group(kind: ClimatologyKind, dates: &[NaiveDate]) -> Vec<Vec<NaiveDate>> {
+    // the dates that go into each slot
+
+    let mut groups = vec![Vec::new(); slots(kind)];
+    for date in dates.iter() {
+        groups[slot(kind, *date)].push(*date);
+    }
+    groups
+}
+
+fn spans(groups: &[Vec<NaiveDate>], halfwidth: i64) -> Vec<Option<(NaiveDate, NaiveDate)>> {
+    // first and last date contributing to each slot once neighbouring slots are smoothed in
+
+    let n = groups.len() as i64;
+    (0..n).map(|s| {
+        let dates = (-halfwidth..=halfwidth).flat_map(|k| groups[(s + k).rem_euclid(n) as usize].iter());
+        let first = dates.clone().min()?;
+        let last = dates.max()?;
+        Some((*first, *last))
+    }).collect()
+}
+
+fn fold(acc: &mut Accumulator, means: &[f64], counts: &[i32], sign: f64) {
+    // add one raw slot's means into a running mean over slots, weighted by their counts, or take them back out for a negative sign
+
+    for i in 0..acc.cells() {
+        if counts[i] > 0 && acc.circular() {
+            // each slot's mean direction counts as many unit vectors as went into it,
+            // unless they cancelled out and left it without one
+            if means[i].is_nan() || means[i] == acc.fill {
+                continue;
+            }
+            acc.add_direction(sign * counts[i] as f64, means[i], i);
+            acc.count[i] += if sign > 0.0 {counts[i]} else {-counts[i]};
+        } else if counts[i] > 0 {
+            acc.sum[i] += sign * means[i] * counts[i] as f64;
+            acc.count[i] += if sign > 0.0 {counts[i]} else {-counts[i]};
+        }
+    }
+}
+
+fn slide<S, W>(accumulators: &mut [Accumulator], n: i64, halfwidth: i64, mut shift: S, mut write: W) -> Result<(), netcdf::error::Error>
+where
+    S: FnMut(&mut [Accumulator], usize, f64) -> Result<(), netcdf::error::Error>,
+    W: FnMut(&[Accumulator], usize) -> Result<(), netcdf::error::Error>
+{
+    // write the running mean over halfwidth slots either side of each of n slots, wrapping around the year;
+    // shift adds a slot in, or takes it back out for a negative sign
+
+    for k in -halfwidth..=halfwidth {
+        shift(accumulators, k.rem_euclid(n) as usize, 1.0)?;
+    }
+    for s in 0..n {
+        write(accumulators, s as usize)?;
+        shift(accumulators, (s - halfwidth).rem_euclid(n) as usize, -1.0)?;
+        shift(accumulators, (s + halfwidth + 1).rem_euclid(n) as usize, 1.0)?;
+    }
+    Ok(())
+}
+
+impl Climatology {
+    pub fn file_name(kind: ClimatologyKind, reference: (i32, i32)) -> String {
+        match kind {
+            ClimatologyKind::Monthly => format!("sla_adt_climatology_monthly_{}-{}.nc", reference.0, reference.1),
+            ClimatologyKind::DayOfYear => format!("sla_adt_climatology_dayofyear_{}-{}.nc", reference.0, reference.1)
+        }
+    }
+
+    pub fn halfwidth(&self) -> i64 {
+        // slots either side of each one averaged in by the smoothing; monthly climatologies aren't smoothed
+
+        match self.kind {
+            ClimatologyKind::Monthly => 0,
+            ClimatologyKind::DayOfYear => self.smoothing / 2
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self.kind {
+            ClimatologyKind::Monthly => format!("monthly climatology over {}-{} in {}", self.reference.0, self.reference.1, self.path.display()),
+            ClimatologyKind::DayOfYear => format!("day-of-year climatology over {}-{} smoothed over {} days in {}", self.reference.0, self.reference.1, 2 * self.halfwidth() + 1, self.path.display())
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(&self, path: &Path, upstream: &netcdf::File, grid: &Grid, groups: &[Vec<NaiveDate>], halfwidth: i64, accumulators: &[Accumulator], provenance: &Provenance, storage: &Storage) -> Result<(), netcdf::error::Error> {
+        // set up a climatology file with no slots written yet, each slot's bounds covering the dates it's built from
+
+        let epoch = lattice::parse_date(lattice::EPOCH)?;
+        let longest = groups.iter().map(|g| g.len()).max().unwrap_or(1) as i64 * (2 * halfwidth + 1);
+
+        let mut outfile = netcdf::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        outfile.add_dimension("time", groups.len())?;
+        outfile.add_attribute("title", "Climatology of Copernicus daily gridded sea level fields")?;
+        output::describe_run(&mut outfile, upstream, provenance)?;
+        outfile.add_attribute("climatology_type", match self.kind {
+            ClimatologyKind::Monthly => "monthly",
+            ClimatologyKind::DayOfYear => "day of year"
+        })?;
+        outfile.add_attribute("reference_period", format!("{}-{}", self.reference.0, self.reference.1))?;
+        if self.kind == ClimatologyKind::DayOfYear {
+            outfile.add_attribute("smoothing_days", (2 * halfwidth + 1) as i32)?;
+        }
+
+        output::declare_grid(&mut outfile, upstream, grid)?;
+        // climatological time, per CF: a representative date, with bounds from the first contributing day to the last
+        let mut time = outfile.add_variable::<f64>("time", &["time"])?;
+        time.add_attribute("standard_name", "time")?;
+        time.add_attribute("long_name", "representative day of the climatological period")?;
+        time.add_attribute("units", format!("days since {} 00:00:00", lattice::EPOCH))?;
+        time.add_attribute("calendar", "standard")?;
+        time.add_attribute("axis", "T")?;
+        time.add_attribute("climatology", "climatology_bounds")?;
+        let labels: Vec<f64> = (0..groups.len()).map(|s| label(self.kind, s).signed_duration_since(epoch).num_days() as f64).collect();
+        time.put_values(&labels, 0)?;
+        outfile.add_dimension("nv", 2)?;
+        let mut bounds = outfile.add_variable::<f64>("climatology_bounds", &["time", "nv"])?;
+        let edges: Vec<f64> = spans(groups, halfwidth).iter().zip(labels.iter()).flat_map(|(span, label)| match span {
+            Some((first, last)) => [first.signed_duration_since(epoch).num_days() as f64, last.signed_duration_since(epoch).num_days() as f64 + 1.0],
+            None => [*label, label + 1.0]
+        }).collect();
+        bounds.put_values(&edges, ..)?;
+
+        output::declare_accumulators(&mut outfile, upstream, accumulators, longest, CELL_METHODS, false, storage)?;
+        outfile.add_attribute("windows_completed", 0)?;
+        Ok(())
+    }
+
+    pub fn check(&self, variables: &[String], tpa_correction: bool) -> Result<(), netcdf::error::Error> {
+        // make sure an existing climatology file is the one this run asks for, beyond the kind and
+        // reference period its name already gives away
+
+        let f = netcdf::open(&self.path).map_err(|e| format!("{}: {}", self.path.display(), e))?;
+        let rebuild = |why: String| -> Result<(), netcdf::error::Error> {
+            Err(format!("{} {}; rerun without --resume to rebuild it", self.path.display(), why).into())
+        };
+
+        if self.kind == ClimatologyKind::DayOfYear {
+            let smoothing = f.attribute("smoothing_days").and_then(|a| a.value().ok()).and_then(|v| Number::try_from(v).ok()).map(|n| n.v as i64);
+            if smoothing != Some(2 * self.halfwidth() + 1) {
+                return rebuild(format!("was smoothed over {} days, not {}", smoothing.map(|s| s.to_string()).unwrap_or_else(|| String::from("an unknown number of")), 2 * self.halfwidth() + 1));
+            }
+        }
+
+        let mut built: Vec<String> = f.variables().filter(|v| {
+            attributes::text(v.attribute("cell_methods")).is_some_and(|s| s == CELL_METHODS)
+        }).map(|v| v.name()).collect();
+        let mut wanted = variables.to_vec();
+        built.sort();
+        wanted.sort();
+        if built != wanted {
+            return rebuild(format!("holds {}, not {}", built.join(","), wanted.join(",")));
+        }
+
+        // anomalies only mean something against a climatology corrected the same way
+        let corrected = attributes::text(f.attribute("tpa_correction")).is_some_and(|s| s == output::TPA_APPLIED);
+        if corrected != tpa_correction {
+            return rebuild(format!("was built {} --tpa-correction", if corrected {"with"} else {"without"}));
+        }
+        Ok(())
+    }
+
+    pub fn write_slot(path: &Path, slot: usize, accumulators: &[Accumulator]) -> Result<(), netcdf::error::Error> {
+        // write one slot and count it as done
+
+        let mut outfile = netcdf::append(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        output::put_accumulators(&mut outfile, slot, accumulators)?;
+        outfile.add_attribute("windows_completed", (slot + 1) as i32)?;
+        Ok(())
+    }
+
+    pub fn smoothed(raw_accumulators: &[Accumulator]) -> Vec<Accumulator> {
+        // fresh accumulators for the smoothed means, which carry no other statistics
+
+        raw_accumulators.iter().map(|acc| {
+            let mut smoothed = Accumulator::new(&acc.name, acc.dims.clone(), acc.fill, &[], Summation::Naive);
+            smoothed.nobs = acc.nobs.clone();
+            smoothed.derived = acc.derived;
+            smoothed
+        }).collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn smooth(&self, raw: &Path, path: &Path, upstream: &netcdf::File, grid: &Grid, groups: &[Vec<NaiveDate>], raw_accumulators: &[Accumulator], provenance: &Provenance, storage: &Storage) -> Result<(), netcdf::error::Error> {
+        // turn unsmoothed slot means into running means over neighbouring slots, weighted by their counts;
+        // the window slides around the year, so each raw slot is read once going in and once going out
+
+        let halfwidth = self.halfwidth();
+        let n = groups.len() as i64;
+        let mut accumulators = Climatology::smoothed(raw_accumulators);
+        self.create(path, upstream, grid, groups, halfwidth, &accumulators, provenance, storage)?;
+
+        let f = netcdf::open(raw).map_err(|e| format!("{}: {}", raw.display(), e))?;
+        let mut means: Vec<f64> = Vec::new();
+        let mut counts: Vec<i32> = Vec::new();
+        let shift = |accumulators: &mut [Accumulator], slot: usize, sign: f64| -> Result<(), netcdf::error::Error> {
+            for acc in accumulators.iter_mut() {
+                let extents = output::time_slice(slot, acc.dims.len());
+                means.resize(acc.cells(), 0.0);
+                counts.resize(acc.cells(), 0);
+                f.variable(&acc.name).ok_or_else(|| format!("{}: could not find variable '{}'", raw.display(), acc.name))?.values_to(&mut means, extents.clone())?;
+                f.variable(&acc.nobs).ok_or_else(|| format!("{}: could not find variable '{}'", raw.display(), acc.nobs))?.values_to(&mut counts, extents)?;
+                fold(acc, &means, &counts, sign);
+            }
+            Ok(())
+        };
+        slide(&mut accumulators, n, halfwidth, shift, |accumulators, s| Climatology::write_slot(path, s, accumulators))
+    }
+
+    pub fn load(&self, date: NaiveDate, names: &[String]) -> Result<Slot, netcdf::error::Error> {
+        // every named variable of the slot a date falls in, read in one go
+
+        let index = slot(self.kind, date);
+        let f = netcdf::open(&self.path).map_err(|e| format!("{}: {}", self.path.display(), e))?;
+        let mut fields = Vec::new();
+        for name in names.iter() {
+            let var = f.variable(name).ok_or_else(|| format!("{}: could not find variable '{}'", self.path.display(), name))?;
+            let dims = var.dimensions().len() - 1;
+            let values = var.values::<f64, _>(output::time_slice(index, dims)).map_err(|e| format!("{}: reading '{}': {}", self.path.display(), name, e))?;
+            fields.push((name.clone(), Packing::from_variable(&var)?, values));
+        }
+        Ok(Slot{index, kind: self.kind, fields})
+    }
+}
+
+// one slot of a climatology held in memory, so that a run of days falling in the same slot
+// take their departures without going back to the file
+
+pub struct Slot {
+    pub index: usize,
+    pub kind: ClimatologyKind,
+    fields: Vec<(String, Packing, Vec<f64>)>
+}
+
+impl Slot {
+    pub fn holds(&self, date: NaiveDate) -> bool {
+        slot(self.kind, date) == self.index
+    }
+
+    pub fn departures(&self, name: &str, packing: &Packing, field: &mut [f64]) -> Result<Packing, netcdf::error::Error> {
+        // turn one day's packed field into departures from this slot in physical units, returning how to
+        // read them back; cells without a climatological value become missing
+
+        let (_, stored, baseline) = self.fields.iter().find(|(n, _, _)| n == name).ok_or_else(|| format!("the climatology has no variable '{}'", name))?;
+        if baseline.len() != field.len() {
+            return Err(format!("the climatology of '{}' has {} cells, but the daily field has {}", name, baseline.len(), field.len()).into());
+        }
+        for (x, b) in field.iter_mut().zip(baseline.iter()) {
+            *x = match (packing.unpack(*x), stored.unpack(*b)) {
+                (Some(x), Some(b)) => x - b,
+                _ => f64::NAN
+            };
+        }
+
+        Ok(Packing::physical())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Derived;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn day_of_year_slots() {
+        // the 29th of February has a slot of its own, so later days land in the same slot in every year
+        assert_eq!(slot(ClimatologyKind::DayOfYear, date(2004, 2, 29)), 59);
+        assert_eq!(slot(ClimatologyKind::DayOfYear, date(2003, 3, 1)), 60);
+        assert_eq!(slot(ClimatologyKind::DayOfYear, date(2004, 3, 1)), 60);
+        assert_eq!(slot(ClimatologyKind::DayOfYear, date(2003, 12, 31)), 365);
+        assert_eq!(slot(ClimatologyKind::Monthly, date(2003, 2, 28)), 1);
+        assert_eq!(slot(ClimatologyKind::Monthly, date(2003, 12, 31)), 11);
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(label(ClimatologyKind::DayOfYear, 59), date(2000, 2, 29));
+        assert_eq!(label(ClimatologyKind::DayOfYear, 365), date(2000, 12, 31));
+        assert_eq!(label(ClimatologyKind::Monthly, 0), date(2000, 1, 16));
+        assert_eq!(label(ClimatologyKind::Monthly, 1), date(2000, 2, 15));
+        assert_eq!(label(ClimatologyKind::Monthly, 11), date(2000, 12, 16));
+    }
+
+    #[test]
+    fn spans_wrap() {
+        let dates = [date(2003, 1, 1), date(2003, 12, 31), date(2004, 1, 1), date(2004, 1, 2), date(2004, 2, 29)];
+        let groups = group(ClimatologyKind::DayOfYear, &dates);
+        let spans = spans(&groups, 1);
+        assert_eq!(spans[0], Some((date(2003, 1, 1), date(2004, 1, 2))));
+        assert_eq!(spans[365], Some((date(2003, 1, 1), date(2004, 1, 1))));
+        assert_eq!(spans[58], Some((date(2004, 2, 29), date(2004, 2, 29))));
+        assert_eq!(spans[100], None);
+    }
+
+    #[test]
+    fn sliding_means() {
+        // every slot is the count-weighted mean of its neighbours, including across the end of the year
+        let n = 366;
+        let halfwidth = 3;
+        let means: Vec<f64> = (0..n).map(|s| s as f64).collect();
+        let counts: Vec<i32> = (0..n).map(|s| if s == 59 {1} else {4}).collect();
+        let mut accumulators = vec![Accumulator::new("sla", vec![(String::from("latitude"), 1)], -1.0, &[], Summation::Naive)];
+        let mut written = Vec::new();
+        slide(&mut accumulators, n as i64, halfwidth, |accumulators, s, sign| {
+            fold(&mut accumulators[0], &means[s..=s], &counts[s..=s], sign);
+            Ok(())
+        }, |accumulators, s| {
+            written.push((s, accumulators[0].count[0], accumulators[0].means()[0]));
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(written.len(), n);
+        for (s, count, mean) in written.iter() {
+            let neighbours: Vec<usize> = (-halfwidth..=halfwidth).map(|k| (*s as i64 + k).rem_euclid(n as i64) as usize).collect();
+            let total: i32 = neighbours.iter().map(|k| counts[*k]).sum();
+            let expected = neighbours.iter().map(|k| means[*k] * counts[*k] as f64).sum::<f64>() / total as f64;
+            assert_eq!(*count, total, "slot {}", s);
+            assert!((mean - expected).abs() < 1e-9, "slot {}: {} against {}", s, mean, expected);
+        }
+        assert_eq!(written[0].1, 28);
+        assert_eq!(written[60].1, 25);
+        assert!((written[0].2 - 4.0 * (363.0 + 364.0 + 365.0 + 0.0 + 1.0 + 2.0 + 3.0) / 28.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sliding_directions() {
+        // directions come back out of the running mean as well as going in
+        let mut acc = Accumulator::new("direction", vec![(String::from("latitude"), 2)], -1.0, &[], Summation::Naive);
+        acc.derived = Some(Derived::Direction);
+        fold(&mut acc, &[350.0, -1.0], &[2, 3], 1.0);
+        fold(&mut acc, &[10.0, 90.0], &[2, 0], 1.0);
+        let means = acc.means();
+        assert!(means[0].min(360.0 - means[0]) < 1e-9, "{}", means[0]);
+        assert_eq!(means[1], -1.0);
+        fold(&mut acc, &[10.0, 90.0], &[2, 0], -1.0);
+        assert!((acc.means()[0] - 350.0).abs() < 1e-9);
+        assert_eq!(acc.count, vec![2, 0]);
+    }
+}
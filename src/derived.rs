@@ -0,0 +1,103 @@
+use crate::attributes::Packing;
+use crate::cli::Derived;
+
+// fields computed from each day's geostrophic velocities before they're averaged,
+// since the mean of a nonlinear function of the daily currents isn't that function of the mean currents
+
+pub fn name(derived: Derived) -> &'static str {
+    match derived {
+        Derived::Eke => "eke",
+        Derived::Speed => "speed",
+        Derived::Direction => "direction"
+    }
+}
+
+pub fn components(derived: Derived) -> (&'static str, &'static str) {
+    // the upstream eastward and northward velocities a derived field is computed from;
+    // eddy kinetic energy comes from the anomalies, speed and direction from the total currents
+
+    match derived {
+        Derived::Eke => ("ugosa", "vgosa"),
+        Derived::Speed | Derived::Direction => ("ugos", "vgos")
+    }
+}
+
+pub fn attributes(derived: Derived) -> Vec<(&'static str, &'static str)> {
+    // what gets said about a derived field in place of the upstream attributes a plain mean carries over
+
+    match derived {
+        Derived::Eke => vec![
+            ("standard_name", "specific_kinetic_energy_of_sea_water"),
+            ("long_name", "eddy kinetic energy, half the sum of the squared daily geostrophic velocity anomalies ugosa and vgosa"),
+            ("units", "m2 s-2")
+        ],
+        Derived::Speed => vec![
+            ("long_name", "speed of the daily absolute geostrophic velocity ugos, vgos"),
+            ("units", "m s-1")
+        ],
+        Derived::Direction => vec![
+            ("standard_name", "direction_of_sea_water_velocity"),
+            ("long_name", "circular mean direction the daily absolute geostrophic velocity ugos, vgos flows towards, clockwise from north"),
+            ("units", "degree")
+        ]
+    }
+}
+
+pub fn compute(derived: Derived, u: &[f64], upacking: &Packing, v: &[f64], vpacking: &Packing, field: &mut [f64]) {
+    // one day's derived field in physical units, NaN wherever either component is missing;
+    // a direction needs a current, so still water has none either
+
+    for ((x, u), v) in field.iter_mut().zip(u.iter()).zip(v.iter()) {
+        *x = match (upacking.unpack(*u), vpacking.unpack(*v)) {
+            (Some(u), Some(v)) => match derived {
+                Derived::Eke => 0.5 * (u * u + v * v),
+                Derived::Speed => u.hypot(v),
+                Derived::Direction if u == 0.0 && v == 0.0 => f64::NAN,
+                Derived::Direction => u.atan2(v).to_degrees().rem_euclid(360.0)
+            },
+            _ => f64::NAN
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn direction(u: f64, v: f64) -> f64 {
+        let mut field = [0.0];
+        compute(Derived::Direction, &[u], &Packing::physical(), &[v], &Packing::physical(), &mut field);
+        field[0]
+    }
+
+    #[test]
+    fn direction_convention() {
+        // where the current flows towards, clockwise from north
+        assert_eq!(direction(0.0, 1.0), 0.0);
+        assert_eq!(direction(1.0, 0.0), 90.0);
+        assert_eq!(direction(0.0, -1.0), 180.0);
+        assert_eq!(direction(-1.0, 0.0), 270.0);
+        assert!((direction(1.0, 1.0) - 45.0).abs() < 1e-12);
+        assert!((direction(-1.0, 1.0) - 315.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn direction_missing() {
+        // still water has no direction, and nor does a cell missing either component
+        assert!(direction(0.0, 0.0).is_nan());
+        assert!(direction(f64::NAN, 1.0).is_nan());
+        assert!(direction(1.0, f64::NAN).is_nan());
+    }
+
+    #[test]
+    fn eke_and_speed() {
+        let packing = Packing{scale_factor: 0.0001, add_offset: 0.0, fill_value: Some(-2147483647.0), missing_value: None, valid_min: None, valid_max: None};
+        let mut field = [0.0; 2];
+        compute(Derived::Eke, &[3000.0, -2147483647.0], &packing, &[-4000.0, 1.0], &packing, &mut field);
+        assert!((field[0] - 0.125).abs() < 1e-12);
+        assert!(field[1].is_nan());
+        compute(Derived::Speed, &[3000.0, 0.0], &packing, &[-4000.0, 0.0], &packing, &mut field);
+        assert!((field[0] - 0.5).abs() < 1e-12);
+        assert_eq!(field[1], 0.0);
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::Duration;
+use chrono::NaiveDate;
+use std::io::Write;
+use std::path::Path;
+
+use crate::attributes::Packing;
+use crate::lattice;
+use crate::output;
+
+// regions each variable is averaged over; cells right on the equator only count towards the global mean
+pub const REGIONS: [&str; 3] = ["global", "north", "south"];
+
+// area means of one averaged variable, one per window and region, None where no cell had a value
+
+pub struct Series {
+    pub name: String,
+    pub long_name: String,
+    pub units: Option<String>,
+    pub values: Vec<[Option<f64>; 3]>
+}
+
+fn in_region(region: usize, latitude: f64) -> bool {
+    match region {
+        0 => true,
+        1 => latitude > 0.0,
+        _ => latitude < 0.0
+    }
+}
+
+pub fn weighted_means(latitude: &[f64], packing: &Packing, field: &[f64], counts: &[i32]) -> [Option<f64>; 3] {
+    // cos(latitude) weighted means of one window's map over each region, counting only cells that had observations;
+    // on a regular grid a cell's area is proportional to the cosine of its latitude
+
+    let row = field.len() / latitude.len().max(1);
+    let mut sums = [0.0; 3];
+    let mut weights = [0.0; 3];
+    for (j, lat) in latitude.iter().enumerate() {
+        let weight = lat.to_radians().cos();
+        let mut sum = 0.0;
+        let mut cells = 0;
+        for i in j * row..(j + 1) * row {
+            if counts[i] <= 0 {
+                continue;
+            }
+            if let Some(value) = packing.unpack(field[i]) {
+                sum += value;
+                cells += 1;
+            }
+        }
+        for region in 0..REGIONS.len() {
+            if in_region(region, *lat) {
+                sums[region] += weight * sum;
+                weights[region] += weight * cells as f64;
+            }
+        }
+    }
+
+    let mut means = [None; 3];
+    for region in 0..REGIONS.len() {
+        if weights[region] > 0.0 {
+            means[region] = Some(sums[region] / weights[region]);
+        }
+    }
+    means
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn write_netcdf(path: &Path, source: &netcdf::File, times: &[f64], bounds: Option<&[f64]>, series: &[Series], correction: &str, inputs: &[String], created: &str, command_line: &str) -> Result<(), netcdf::error::Error> {
+    // one time series per variable and region, on the same time axis as the averaged files
+
+    let mut outfile = netcdf::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    output::stamp(&mut outfile, source, &output::AVERAGED_ATTRIBUTES, created, command_line)?;
+    outfile.add_attribute("title", "Area weighted global and hemispheric means of averaged Copernicus gridded sea level fields")?;
+    outfile.add_attribute("area_weighting", "cos(latitude) of every cell with observations in the window; cells on the equator count towards the global mean only")?;
+    outfile.add_attribute("tpa_correction", correction)?;
+    outfile.add_attribute("input_files", inputs.join(","))?;
+
+    outfile.add_dimension("time", times.len())?;
+    let mut time = outfile.add_variable::<f64>("time", &["time"])?;
+    time.add_attribute("standard_name", "time")?;
+    time.add_attribute("long_name", "label date of the averaging window")?;
+    time.add_attribute("units", format!("days since {} 00:00:00", lattice::EPOCH))?;
+    time.add_attribute("calendar", "standard")?;
+    time.add_attribute("axis", "T")?;
+    if bounds.is_some() {
+        time.add_attribute("bounds", "time_bnds")?;
+    }
+    time.put_values(times, ..)?;
+    if let Some(bounds) = bounds {
+        outfile.add_dimension("nv", 2)?;
+        outfile.add_variable::<f64>("time_bnds", &["time", "nv"])?.put_values(bounds, ..)?;
+    }
+
+    for s in series.iter() {
+        for (region, label) in REGIONS.iter().enumerate() {
+            let mut var = outfile.add_variable::<f64>(&format!("{}_{}", s.name, label), &["time"])?;
+            var.set_fill_value(output::SENTINEL)?;
+            var.add_attribute("long_name", format!("cos(latitude) weighted {} mean of {}", if region == 0 {String::from("global")} else {format!("{}ern hemisphere", label)}, s.long_name))?;
+            if let Some(units) = &s.units {
+                var.add_attribute("units", units.as_str())?;
+            }
+            var.add_attribute("cell_methods", "area: mean")?;
+            let values: Vec<f64> = s.values.iter().map(|v| v[region].unwrap_or(output::SENTINEL)).collect();
+            var.put_values(&values, ..)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn write_csv(path: &Path, epoch: NaiveDate, times: &[f64], series: &[Series]) -> Result<(), netcdf::error::Error> {
+    // the same series as a table, one row per window labelled by its date, with empty fields where there's no mean
+
+    let mut table = String::from("date");
+    for s in series.iter() {
+        for label in REGIONS.iter() {
+            table.push_str(&format!(",{}_{}", s.name, label));
+        }
+    }
+    table.push('\n');
+    for (w, t) in times.iter().enumerate() {
+        table.push_str(&(epoch + Duration::days(t.round() as i64)).to_string());
+        for s in series.iter() {
+            for value in s.values[w].iter() {
+                table.push(',');
+                if let Some(value) = value {
+                    table.push_str(&value.to_string());
+                }
+            }
+        }
+        table.push('\n');
+    }
+
+    let mut file = std::fs::File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    file.write_all(table.as_bytes()).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighted_means_by_region() {
+        // rows at 60S, the equator and 60N; cells without observations are left out whatever their value
+        let latitude = [-60.0, 0.0, 60.0];
+        let field = [1.0, 2.0, 10.0, 99.0, 4.0, 100.0];
+        let counts = [7, 7, 7, 0, 7, 0];
+        let means = weighted_means(&latitude, &Packing::physical(), &field, &counts);
+        // the equator row weighs twice what the others do, and only counts towards the global mean
+        assert!((means[0].unwrap() - (0.5 * 3.0 + 10.0 + 0.5 * 4.0) / (0.5 * 2.0 + 1.0 + 0.5)).abs() < 1e-12);
+        assert!((means[1].unwrap() - 4.0).abs() < 1e-12);
+        assert!((means[2].unwrap() - 1.5).abs() < 1e-12);
+
+        let counts = [7, 7, 7, 0, 0, 0];
+        assert_eq!(weighted_means(&latitude, &Packing::physical(), &field, &counts)[1], None);
+    }
+}
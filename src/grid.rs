@@ -0,0 +1,56 @@
+use std::path::Path;
+
+// largest difference between coordinate values still taken to be the same grid, in degrees;
+// well below any product's spacing but above float noise between producers
+const TOLERANCE: f64 = 1e-6;
+
+// the horizontal grid of a run, read from the first daily input and expected of every other one
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grid {
+    pub latitude: Vec<f64>,
+    pub longitude: Vec<f64>
+}
+
+fn coordinate(f: &netcdf::File, name: &str, path: &Path) -> Result<Vec<f64>, netcdf::error::Error> {
+    // values of a one dimensional coordinate variable
+
+    let var = f.variable(name).ok_or_else(|| format!("{}: could not find coordinate variable '{}'", path.display(), name))?;
+    if var.dimensions().len() != 1 {
+        return Err(format!("{}: coordinate variable '{}' is not one dimensional", path.display(), name).into());
+    }
+    var.values::<f64, _>(..).map_err(|e| format!("{}: reading '{}': {}", path.display(), name, e).into())
+}
+
+fn same(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() <= TOLERANCE)
+}
+
+impl Grid {
+    pub fn from_file(f: &netcdf::File, path: &Path) -> Result<Grid, netcdf::error::Error> {
+        Ok(Grid{
+            latitude: coordinate(f, "latitude", path)?,
+            longitude: coordinate(f, "longitude", path)?
+        })
+    }
+
+    pub fn check(&self, f: &netcdf::File, path: &Path) -> Result<(), netcdf::error::Error> {
+        // make sure another daily file is on this grid
+
+        let other = Grid::from_file(f, path)?;
+        if !same(&self.latitude, &other.latitude) || !same(&self.longitude, &other.longitude) {
+            return Err(format!("{}: grid is {}, but the run started on {}", path.display(), other.describe(), self.describe()).into());
+        }
+        Ok(())
+    }
+
+    pub fn describe(&self) -> String {
+        // shape and extent, for messages
+
+        let extent = |v: &[f64]| match (v.first(), v.last()) {
+            (Some(first), Some(last)) => format!("{} to {}", first, last),
+            _ => String::from("empty")
+        };
+        format!("{} latitudes ({}) x {} longitudes ({})", self.latitude.len(), extent(&self.latitude), self.longitude.len(), extent(&self.longitude))
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+// the delayed-time two-satellite product the weekly record was built from
+pub const DEFAULT_TEMPLATE: &str = "dt_global_twosat_phy_l4_{date}_v{version}.nc";
+pub const DEFAULT_VERSION: &str = "DT2021";
+
+#[derive(Debug, PartialEq)]
+pub enum Token {
+    Literal(String),
+    Date,
+    Version,
+    Wildcard
+}
+
+fn tokenize(template: &str) -> Result<Vec<Token>, netcdf::error::Error> {
+    // split a filename template into literal text and the {date}, {version} and * placeholders
+
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        let token = if rest.starts_with("{date}") {
+            Some((Token::Date, "{date}".len()))
+        } else if rest.starts_with("{version}") {
+            Some((Token::Version, "{version}".len()))
+        } else if c == '*' {
+            Some((Token::Wildcard, 1))
+        } else if c == '{' || c == '}' {
+            return Err(format!("unknown placeholder in filename template '{}', expected {{date}} or {{version}}", template).into());
+        } else {
+            None
+        };
+
+        match token {
+            Some((t, len)) => {
+                if !literal.is_empty() {
+                    tokens.push(Token::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(t);
+                rest = &rest[len..];
+            },
+            None => {
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !literal.is_empty() {
+        tokens.push(Token::Literal(literal));
+    }
+
+    if tokens.iter().filter(|t| **t == Token::Date).count() != 1 {
+        return Err(format!("filename template '{}' must contain {{date}} exactly once", template).into());
+    }
+    Ok(tokens)
+}
+
+fn matches(tokens: &[Token], name: &str, version: Option<&str>, date: Option<NaiveDate>) -> Option<NaiveDate> {
+    // match a filename against a tokenized template, returning the date it carries;
+    // {version} matches anything unless a specific version was asked for
+
+    match tokens.first() {
+        None => if name.is_empty() {date} else {None},
+        Some(Token::Literal(l)) => matches(&tokens[1..], name.strip_prefix(l.as_str())?, version, date),
+        Some(Token::Date) => {
+            let day = NaiveDate::parse_from_str(name.get(0..8)?, "%Y%m%d").ok()?;
+            matches(&tokens[1..], &name[8..], version, Some(day))
+        },
+        Some(Token::Version) if version.is_some() => matches(&tokens[1..], name.strip_prefix(version?)?, version, date),
+        Some(Token::Version) | Some(Token::Wildcard) => {
+            // try every possible length for the free text, shortest first
+            (0..=name.len()).filter(|i| name.is_char_boundary(*i)).find_map(|i| matches(&tokens[1..], &name[i..], version, date))
+        }
+    }
+}
+
+pub enum Inputs {
+    // daily file paths built directly from a template
+    Template {
+        dir: PathBuf,
+        tokens: Vec<Token>,
+        version: String
+    },
+    // daily files found by scanning a directory
+    Discovered {
+        dir: PathBuf,
+        files: BTreeMap<NaiveDate, PathBuf>
+    }
+}
+
+impl Inputs {
+    pub fn from_template(dir: &Path, template: &str, version: &str) -> Result<Inputs, netcdf::error::Error> {
+        let tokens = tokenize(template)?;
+        if tokens.contains(&Token::Wildcard) {
+            return Err(format!("filename template '{}' has a wildcard, which only works with --discover", template).into());
+        }
+
+        Ok(Inputs::Template{dir: dir.to_path_buf(), tokens, version: String::from(version)})
+    }
+
+    pub fn discover(dir: &Path, template: &str, version: Option<&str>) -> Result<Inputs, netcdf::error::Error> {
+        // map every date to the file in dir matching the template
+
+        let tokens = tokenize(template)?;
+        let entries = std::fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let mut files = BTreeMap::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("{}: {}", dir.display(), e))?;
+            let name = entry.file_name();
+            let date = match name.to_str().and_then(|n| matches(&tokens, n, version, None)) {
+                Some(date) => date,
+                None => continue
+            };
+            if let Some(previous) = files.insert(date, entry.path()) {
+                return Err(format!("both {} and {} match {} for {}; pass --product-version to choose one", previous.display(), entry.path().display(), template, date).into());
+            }
+        }
+
+        Ok(Inputs::Discovered{dir: dir.to_path_buf(), files})
+    }
+
+    pub fn path(&self, date: NaiveDate) -> Result<PathBuf, netcdf::error::Error> {
+        // the daily file holding this date
+
+        match self {
+            Inputs::Template{dir, tokens, version} => {
+                let mut name = String::new();
+                for token in tokens.iter() {
+                    match token {
+                        Token::Literal(l) => name.push_str(l),
+                        Token::Date => name.push_str(&date.format("%Y%m%d").to_string()),
+                        Token::Version => name.push_str(version),
+                        Token::Wildcard => unreachable!("wildcards are rejected in from_template")
+                    }
+                }
+                Ok(dir.join(name))
+            },
+            Inputs::Discovered{dir, files} => {
+                match files.get(&date) {
+                    Some(path) => Ok(path.clone()),
+                    None => Err(format!("{}: no daily file found for {}", dir.display(), date).into())
+                }
+            }
+        }
+    }
+
+    pub fn available(&self, date: NaiveDate) -> Option<PathBuf> {
+        // the daily file holding this date, if there is one on disk
+
+        self.path(date).ok().filter(|path| path.exists())
+    }
+
+    pub fn describe(&self) -> String {
+        // one line summary of where inputs come from
+
+        match self {
+            Inputs::Template{dir, ..} => format!("daily files named by template under {}", dir.display()),
+            Inputs::Discovered{dir, files} => match (files.keys().next(), files.keys().last()) {
+                (Some(first), Some(last)) => format!("{} daily files discovered under {}, {} to {}", files.len(), dir.display(), first, last),
+                _ => format!("no daily files discovered under {}", dir.display())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()
+    }
+
+    #[test]
+    fn tokenize_default() {
+        assert_eq!(tokenize(DEFAULT_TEMPLATE).unwrap(), vec![
+            Token::Literal(String::from("dt_global_twosat_phy_l4_")),
+            Token::Date,
+            Token::Literal(String::from("_v")),
+            Token::Version,
+            Token::Literal(String::from(".nc"))
+        ]);
+        assert_eq!(tokenize("{date}*.nc").unwrap(), vec![Token::Date, Token::Wildcard, Token::Literal(String::from(".nc"))]);
+    }
+
+    #[test]
+    fn tokenize_rejects() {
+        assert!(tokenize("ssh_{day}.nc").is_err());
+        assert!(tokenize("ssh_{date").is_err());
+        assert!(tokenize("ssh.nc").is_err());
+        assert!(tokenize("{date}_{date}.nc").is_err());
+    }
+
+    #[test]
+    fn matches_template() {
+        let tokens = tokenize(DEFAULT_TEMPLATE).unwrap();
+        let name = "dt_global_twosat_phy_l4_19930103_vDT2021.nc";
+        assert_eq!(matches(&tokens, name, None, None), date("1993-01-03"));
+        assert_eq!(matches(&tokens, name, Some("DT2021"), None), date("1993-01-03"));
+        assert_eq!(matches(&tokens, name, Some("DT2024"), None), None);
+        assert_eq!(matches(&tokens, "dt_global_twosat_phy_l4_19930103_vDT2021.nc.tmp", None, None), None);
+        assert_eq!(matches(&tokens, "dt_global_twosat_phy_l4_19931303_vDT2021.nc", None, None), None);
+        assert_eq!(matches(&tokens, "dt_global_allsat_phy_l4_19930103_vDT2021.nc", None, None), None);
+    }
+
+    #[test]
+    fn matches_wildcard() {
+        let tokens = tokenize("nrt_global_allsat_phy_l4_{date}_*.nc").unwrap();
+        assert_eq!(matches(&tokens, "nrt_global_allsat_phy_l4_20230105_20230111.nc", None, None), date("2023-01-05"));
+        assert_eq!(matches(&tokens, "nrt_global_allsat_phy_l4_20230105_.nc", None, None), date("2023-01-05"));
+        assert_eq!(matches(&tokens, "nrt_global_allsat_phy_l4_20230105.nc", None, None), None);
+    }
+}
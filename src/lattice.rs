@@ -0,0 +1,278 @@
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::NaiveDate;
+
+use crate::cli::Alignment;
+use crate::cli::Period;
+
+// the weekly record starts on the first Sunday of 1993
+pub const ANCHOR: &str = "1993-01-03";
+// first day of the DT2021 holdings; no window reaches back before it
+pub const RECORD_START: &str = "1993-01-01";
+// last day of the DT2021 holdings the original hard-coded table was built from
+pub const RECORD_END: &str = "2022-07-30";
+// all timestamps are reported as days since this epoch
+pub const EPOCH: &str = "1993-01-01";
+
+pub fn parse_date(date: &str) -> Result<NaiveDate, netcdf::error::Error> {
+    // parse a date given either as yyyy-mm-dd, yyyymmdd, or a full RFC3339 timestamp like "1993-02-07T00:00:00.000Z"
+
+    let day = date.get(0..10).unwrap_or(date);
+    NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y%m%d"))
+        .map_err(|_| format!("could not parse date '{}', expected yyyy-mm-dd or yyyymmdd", date).into())
+}
+
+// a run of consecutive days averaged together, reported under its label date
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    pub label: NaiveDate,
+    pub start: NaiveDate,
+    pub length: i64
+}
+
+impl Window {
+    pub fn dates(&self) -> Vec<NaiveDate> {
+        // every day covered by this window, in order
+
+        (0..self.length).map(|i| self.start + Duration::days(i)).collect()
+    }
+
+    pub fn timestamp(&self, epoch: NaiveDate) -> i64 {
+        // days between the epoch and this window's label date
+
+        self.label.signed_duration_since(epoch).num_days()
+    }
+
+    pub fn end(&self) -> NaiveDate {
+        self.start + Duration::days(self.length - 1)
+    }
+}
+
+// windows of a fixed number of days with labels stepping by stride from the anchor, or calendar months or years
+pub struct Lattice {
+    pub anchor: NaiveDate,
+    pub period: Period,
+    pub length: i64,
+    pub stride: i64,
+    pub alignment: Alignment,
+    pub begin: NaiveDate,
+    pub end: NaiveDate
+}
+
+fn next_calendar(period: Period, start: NaiveDate) -> NaiveDate {
+    // first day of the month or year after the one starting on start
+
+    let (year, month) = match period {
+        Period::Year => (start.year() + 1, 1),
+        _ if start.month() == 12 => (start.year() + 1, 1),
+        _ => (start.year(), start.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1).expect("first of the month is always a valid date")
+}
+
+impl Lattice {
+    pub fn new(anchor: NaiveDate, period: Period, length: i64, stride: i64, alignment: Alignment, begin: NaiveDate, end: NaiveDate) -> Lattice {
+        Lattice{anchor, period, length, stride, alignment, begin, end}
+    }
+
+    pub fn window(&self, label: NaiveDate) -> Window {
+        // the days averaged for a label date: starting on it, around it, or ending on it;
+        // a centered window of even length has its extra day after the label
+
+        let start = match self.alignment {
+            Alignment::Forward => label,
+            Alignment::Centered => label - Duration::days((self.length - 1) / 2),
+            Alignment::Backward => label - Duration::days(self.length - 1)
+        };
+        Window{label, start, length: self.length}
+    }
+
+    fn calendar(&self, start: NaiveDate) -> Window {
+        // a whole month or year, labelled by its first, middle or last day
+
+        let length = next_calendar(self.period, start).signed_duration_since(start).num_days();
+        let label = match self.alignment {
+            Alignment::Forward => start,
+            Alignment::Centered => start + Duration::days((length - 1) / 2),
+            Alignment::Backward => start + Duration::days(length - 1)
+        };
+        Window{label, start, length}
+    }
+
+    pub fn windows(&self) -> Vec<Window> {
+        // every window that fits entirely between the begin and end dates
+
+        let mut windows = Vec::new();
+        match self.period {
+            Period::Days => {
+                let mut label = self.anchor;
+                loop {
+                    let window = self.window(label);
+                    if window.end() > self.end {
+                        break;
+                    }
+                    if window.start >= self.begin {
+                        windows.push(window);
+                    }
+                    label += Duration::days(self.stride);
+                }
+            },
+            Period::Month | Period::Year => {
+                let mut start = NaiveDate::from_ymd_opt(self.begin.year(), if self.period == Period::Year {1} else {self.begin.month()}, 1).expect("first of the month is always a valid date");
+                loop {
+                    let window = self.calendar(start);
+                    if window.end() > self.end {
+                        break;
+                    }
+                    if window.start >= self.begin {
+                        windows.push(window);
+                    }
+                    start = next_calendar(self.period, start);
+                }
+            }
+        }
+
+        windows
+    }
+
+    pub fn year(&self, year: i32) -> Vec<Window> {
+        // windows whose label date falls in the given calendar year
+
+        self.windows().into_iter().filter(|w| w.label.year() == year).collect()
+    }
+
+    pub fn years(&self) -> Vec<i32> {
+        // every calendar year that has at least one window
+
+        let mut years: Vec<i32> = self.windows().iter().map(|w| w.label.year()).collect();
+        years.dedup();
+        years
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weekly() -> Lattice {
+        // the original table: 7 day windows starting on each Sunday of the record
+        Lattice::new(parse_date(ANCHOR).unwrap(), Period::Days, 7, 7, Alignment::Forward, parse_date(RECORD_START).unwrap(), parse_date(RECORD_END).unwrap())
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        parse_date(s).unwrap()
+    }
+
+    #[test]
+    fn aligned_windows() {
+        // an odd window sits evenly around its label, an even one has its extra day after it
+        let window = |length, alignment| Lattice::new(date(ANCHOR), Period::Days, length, length, alignment, date(RECORD_START), date(RECORD_END)).window(date("1994-01-09"));
+        assert_eq!(window(7, Alignment::Forward), Window{label: date("1994-01-09"), start: date("1994-01-09"), length: 7});
+        assert_eq!(window(7, Alignment::Centered), Window{label: date("1994-01-09"), start: date("1994-01-06"), length: 7});
+        assert_eq!(window(7, Alignment::Backward), Window{label: date("1994-01-09"), start: date("1994-01-03"), length: 7});
+        assert_eq!(window(7, Alignment::Centered).end(), date("1994-01-12"));
+        assert_eq!(window(8, Alignment::Centered).start, date("1994-01-06"));
+        assert_eq!(window(8, Alignment::Centered).end(), date("1994-01-13"));
+        assert_eq!(window(1, Alignment::Centered).start, date("1994-01-09"));
+    }
+
+    #[test]
+    fn aligned_record_start() {
+        // windows reaching back before the record are dropped, so centered and backward runs start a week later
+        let first = |alignment| Lattice::new(date(ANCHOR), Period::Days, 7, 7, alignment, date(RECORD_START), date(RECORD_END)).windows()[0].clone();
+        assert_eq!(first(Alignment::Forward).label, date("1993-01-03"));
+        assert_eq!(first(Alignment::Centered).label, date("1993-01-10"));
+        assert_eq!(first(Alignment::Centered).start, date("1993-01-07"));
+        assert_eq!(first(Alignment::Backward).label, date("1993-01-10"));
+        assert_eq!(first(Alignment::Backward).start, date("1993-01-04"));
+    }
+
+    #[test]
+    fn weekly_years() {
+        let lattice = weekly();
+        assert_eq!(lattice.years(), (1993..=2022).collect::<Vec<i32>>());
+        for year in 1993..=2021 {
+            let windows = lattice.year(year);
+            let expected = if [1995, 2000, 2006, 2012, 2017].contains(&year) {53} else {52};
+            assert_eq!(windows.len(), expected, "windows in {}", year);
+
+            // the first and last Sundays of the year
+            let first = windows.first().unwrap().label;
+            let last = windows.last().unwrap().label;
+            assert_eq!(first.weekday(), chrono::Weekday::Sun);
+            assert!(first.ordinal() <= 7, "{} starts on {}", year, first);
+            assert_eq!(last.weekday(), chrono::Weekday::Sun);
+            assert!(last + Duration::days(7) > NaiveDate::from_ymd_opt(year, 12, 31).unwrap(), "{} ends on {}", year, last);
+        }
+        assert_eq!(lattice.year(1993).first().unwrap().label, parse_date("1993-01-03").unwrap());
+        assert_eq!(lattice.year(2021).last().unwrap().label, parse_date("2021-12-26").unwrap());
+    }
+
+    #[test]
+    fn weekly_record_end() {
+        // the last window has to fit before the end of the record
+        let windows = weekly().year(2022);
+        assert_eq!(windows.first().unwrap().label, parse_date("2022-01-02").unwrap());
+        assert_eq!(windows.last().unwrap().label, parse_date("2022-07-24").unwrap());
+        assert_eq!(windows.last().unwrap().end(), parse_date(RECORD_END).unwrap());
+    }
+
+    fn calendar(period: Period, alignment: Alignment) -> Lattice {
+        Lattice::new(date(ANCHOR), period, 7, 7, alignment, date(RECORD_START), date(RECORD_END))
+    }
+
+    #[test]
+    fn monthly_windows() {
+        let lattice = calendar(Period::Month, Alignment::Forward);
+        let windows = lattice.windows();
+        assert_eq!(windows.first().unwrap(), &Window{label: date("1993-01-01"), start: date("1993-01-01"), length: 31});
+        // July 2022 runs past the end of the record
+        assert_eq!(windows.last().unwrap().start, date("2022-06-01"));
+        assert_eq!(lattice.year(2022).len(), 6);
+        assert_eq!(windows.len(), 29 * 12 + 6);
+
+        // February has 29 days in leap years only
+        let february = |year| lattice.year(year)[1].clone();
+        assert_eq!(february(1996).length, 29);
+        assert_eq!(february(2000).length, 29);
+        assert_eq!(february(1999).length, 28);
+        assert_eq!(february(1996).end(), date("1996-02-29"));
+    }
+
+    #[test]
+    fn calendar_labels() {
+        let february = |alignment| calendar(Period::Month, alignment).year(2000)[1].label;
+        assert_eq!(february(Alignment::Forward), date("2000-02-01"));
+        assert_eq!(february(Alignment::Centered), date("2000-02-15"));
+        assert_eq!(february(Alignment::Backward), date("2000-02-29"));
+        let year = |alignment| calendar(Period::Year, alignment).year(1996)[0].clone();
+        assert_eq!(year(Alignment::Forward).length, 366);
+        assert_eq!(year(Alignment::Centered).label, date("1996-07-01"));
+        assert_eq!(year(Alignment::Backward).label, date("1996-12-31"));
+    }
+
+    #[test]
+    fn annual_windows() {
+        // 2022 isn't complete in the record, so has no annual window
+        let lattice = calendar(Period::Year, Alignment::Forward);
+        assert_eq!(lattice.years(), (1993..=2021).collect::<Vec<i32>>());
+        assert!(lattice.year(2022).is_empty());
+        assert_eq!(lattice.year(1995)[0].length, 365);
+        let extended = Lattice::new(date(ANCHOR), Period::Year, 7, 7, Alignment::Forward, date(RECORD_START), date("2022-12-31"));
+        assert_eq!(extended.years().last(), Some(&2022));
+    }
+
+    #[test]
+    fn running_windows() {
+        // a 7-day running mean gets a window every day, each overlapping the last by six days
+        let lattice = Lattice::new(date(ANCHOR), Period::Days, 7, 1, Alignment::Centered, date(RECORD_START), date(RECORD_END));
+        let windows = lattice.year(1993);
+        assert_eq!(windows[0], Window{label: date("1993-01-04"), start: date("1993-01-01"), length: 7});
+        assert_eq!(windows[1].start, date("1993-01-02"));
+        assert_eq!(windows.last().unwrap().label, date("1993-12-31"));
+        assert_eq!(windows.len(), 365 - 3);
+        assert!(windows.windows(2).all(|pair| pair[1].label - pair[0].label == Duration::days(1)));
+        assert_eq!(lattice.windows().last().unwrap().end(), date(RECORD_END));
+    }
+}
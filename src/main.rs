@@ -1,20 +1,57 @@
-use netcdf;
+mod accumulate;
+mod attributes;
+mod checksum;
+mod climatology;
+mod derived;
+mod cli;
+mod gmsl;
+mod grid;
+mod inputs;
+mod lattice;
+mod output;
+mod trend;
+
+use accumulate::Accumulator;
+use attributes::Packing;
+use chrono::NaiveDate;
+use climatology::Climatology;
+use climatology::Slot;
 use chrono::Utc;
-use chrono::TimeZone;
-use chrono::Duration;
 use chrono::Datelike;
 use chrono::Timelike;
-use chrono::DateTime;
-use std::env;
-use std::error::Error;
-
+use cli::AveragedArgs;
+use cli::Chunking;
+use cli::Cli;
+use cli::ClimatologyArgs;
+use cli::Command;
+use cli::Derived;
+use cli::GmslArgs;
+use cli::MissingPolicy;
+use cli::Precision;
+use cli::RunArgs;
+use cli::Summation;
+use cli::TrendArgs;
+use gmsl::Series;
+use grid::Grid;
+use inputs::Inputs;
+use lattice::Lattice;
+use lattice::Window;
+use output::Layout;
+use output::Provenance;
+use output::Storage;
+use trend::Regression;
+use rayon::prelude::*;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[allow(dead_code)] // shared with the db-population branch
 fn tidylon(longitude: f64) -> f64{
     // map longitude on [0,360] to [-180,180], required for mongo indexing
     if longitude <= 180.0{
-        return longitude;
+        longitude
     }
     else{
-        return longitude-360.0;
+        longitude-360.0
     }
 }
 
@@ -22,30 +59,11 @@ fn nowstring() -> String{
     // returns a String representing the current ISO8601 datetime
 
     let now = Utc::now();
-    return format!("{}-{:02}-{:02}T{:02}:{:02}:{:02}Z", now.year(), now.month(), now.day(), now.hour(), now.minute(), now.second());
+    format!("{}-{:02}-{:02}T{:02}:{:02}:{:02}Z", now.year(), now.month(), now.day(), now.hour(), now.minute(), now.second())
 }
 
-// impementing a foreign trait on a forein struct //////////
-// per the advice in https://stackoverflow.com/questions/76277096/deconstructing-enums-in-rust/76277117#76277117
-
-struct Wrapper{
-    s: String
-}
-
-impl std::convert::TryFrom<netcdf::attribute::AttrValue> for Wrapper {
-    type Error = &'static str;
-
-    fn try_from(value: netcdf::attribute::AttrValue) -> Result<Self, Self::Error> {
-
-        if let netcdf::attribute::AttrValue::Str(v) = value {
-            Ok(Wrapper{s: String::from(v)} )
-        } else {
-            Err("nope")
-        }
-    }
-}
-////////////////////
-
+#[allow(dead_code)] // shared with the db-population branch
+#[allow(clippy::needless_range_loop)]
 fn find_basin(basins: &netcdf::Variable, longitude: f64, latitude: f64) -> i32 {    
     let lonplus = (longitude-0.5).ceil()+0.5;
     let lonminus = (longitude-0.5).floor()+0.5;
@@ -87,407 +105,773 @@ fn find_basin(basins: &netcdf::Variable, longitude: f64, latitude: f64) -> i32 {
     }   
 }
 
-fn timewindow(center: &str, period: i64) -> Vec<String> {
-    // given a string specifying the central date in the format "1993-02-07T00:00:00.000Z",
-    // produce a list of strings for the days + period that date in the format yyyymmdd
-
-    let rfc3339 = DateTime::parse_from_rfc3339(center).unwrap();
-    let mut dates = Vec::new();
-    for i in 0..period {
-        let d = rfc3339 + Duration::days(i);
-        dates.push(format!("{}{:02}{:02}", d.year(), d.month(), d.day()));
-    }
-
-    return dates
-    
-}
-
-fn main() -> Result<(),netcdf::error::Error> {
-
-    // command line argument extraction
-    let args: Vec<String> = env::args().collect();
-    let iter = args[1].parse::<usize>().unwrap();;
-
-    let timeseries = vec![
-        vec!["1993-01-03T00:00:00.000Z","1993-01-10T00:00:00.000Z","1993-01-17T00:00:00.000Z","1993-01-24T00:00:00.000Z","1993-01-31T00:00:00.000Z","1993-02-07T00:00:00.000Z","1993-02-14T00:00:00.000Z","1993-02-21T00:00:00.000Z","1993-02-28T00:00:00.000Z","1993-03-07T00:00:00.000Z","1993-03-14T00:00:00.000Z","1993-03-21T00:00:00.000Z","1993-03-28T00:00:00.000Z","1993-04-04T00:00:00.000Z","1993-04-11T00:00:00.000Z","1993-04-18T00:00:00.000Z","1993-04-25T00:00:00.000Z","1993-05-02T00:00:00.000Z","1993-05-09T00:00:00.000Z","1993-05-16T00:00:00.000Z","1993-05-23T00:00:00.000Z","1993-05-30T00:00:00.000Z","1993-06-06T00:00:00.000Z","1993-06-13T00:00:00.000Z","1993-06-20T00:00:00.000Z","1993-06-27T00:00:00.000Z","1993-07-04T00:00:00.000Z","1993-07-11T00:00:00.000Z","1993-07-18T00:00:00.000Z","1993-07-25T00:00:00.000Z","1993-08-01T00:00:00.000Z","1993-08-08T00:00:00.000Z","1993-08-15T00:00:00.000Z","1993-08-22T00:00:00.000Z","1993-08-29T00:00:00.000Z","1993-09-05T00:00:00.000Z","1993-09-12T00:00:00.000Z","1993-09-19T00:00:00.000Z","1993-09-26T00:00:00.000Z","1993-10-03T00:00:00.000Z","1993-10-10T00:00:00.000Z","1993-10-17T00:00:00.000Z","1993-10-24T00:00:00.000Z","1993-10-31T00:00:00.000Z","1993-11-07T00:00:00.000Z","1993-11-14T00:00:00.000Z","1993-11-21T00:00:00.000Z","1993-11-28T00:00:00.000Z","1993-12-05T00:00:00.000Z","1993-12-12T00:00:00.000Z","1993-12-19T00:00:00.000Z","1993-12-26T00:00:00.000Z"],
-        vec!["1994-01-02T00:00:00.000Z","1994-01-09T00:00:00.000Z","1994-01-16T00:00:00.000Z","1994-01-23T00:00:00.000Z","1994-01-30T00:00:00.000Z","1994-02-06T00:00:00.000Z","1994-02-13T00:00:00.000Z","1994-02-20T00:00:00.000Z","1994-02-27T00:00:00.000Z","1994-03-06T00:00:00.000Z","1994-03-13T00:00:00.000Z","1994-03-20T00:00:00.000Z","1994-03-27T00:00:00.000Z","1994-04-03T00:00:00.000Z","1994-04-10T00:00:00.000Z","1994-04-17T00:00:00.000Z","1994-04-24T00:00:00.000Z","1994-05-01T00:00:00.000Z","1994-05-08T00:00:00.000Z","1994-05-15T00:00:00.000Z","1994-05-22T00:00:00.000Z","1994-05-29T00:00:00.000Z","1994-06-05T00:00:00.000Z","1994-06-12T00:00:00.000Z","1994-06-19T00:00:00.000Z","1994-06-26T00:00:00.000Z","1994-07-03T00:00:00.000Z","1994-07-10T00:00:00.000Z","1994-07-17T00:00:00.000Z","1994-07-24T00:00:00.000Z","1994-07-31T00:00:00.000Z","1994-08-07T00:00:00.000Z","1994-08-14T00:00:00.000Z","1994-08-21T00:00:00.000Z","1994-08-28T00:00:00.000Z","1994-09-04T00:00:00.000Z","1994-09-11T00:00:00.000Z","1994-09-18T00:00:00.000Z","1994-09-25T00:00:00.000Z","1994-10-02T00:00:00.000Z","1994-10-09T00:00:00.000Z","1994-10-16T00:00:00.000Z","1994-10-23T00:00:00.000Z","1994-10-30T00:00:00.000Z","1994-11-06T00:00:00.000Z","1994-11-13T00:00:00.000Z","1994-11-20T00:00:00.000Z","1994-11-27T00:00:00.000Z","1994-12-04T00:00:00.000Z","1994-12-11T00:00:00.000Z","1994-12-18T00:00:00.000Z","1994-12-25T00:00:00.000Z"],
-        vec!["1995-01-01T00:00:00.000Z","1995-01-08T00:00:00.000Z","1995-01-15T00:00:00.000Z","1995-01-22T00:00:00.000Z","1995-01-29T00:00:00.000Z","1995-02-05T00:00:00.000Z","1995-02-12T00:00:00.000Z","1995-02-19T00:00:00.000Z","1995-02-26T00:00:00.000Z","1995-03-05T00:00:00.000Z","1995-03-12T00:00:00.000Z","1995-03-19T00:00:00.000Z","1995-03-26T00:00:00.000Z","1995-04-02T00:00:00.000Z","1995-04-09T00:00:00.000Z","1995-04-16T00:00:00.000Z","1995-04-23T00:00:00.000Z","1995-04-30T00:00:00.000Z","1995-05-07T00:00:00.000Z","1995-05-14T00:00:00.000Z","1995-05-21T00:00:00.000Z","1995-05-28T00:00:00.000Z","1995-06-04T00:00:00.000Z","1995-06-11T00:00:00.000Z","1995-06-18T00:00:00.000Z","1995-06-25T00:00:00.000Z","1995-07-02T00:00:00.000Z","1995-07-09T00:00:00.000Z","1995-07-16T00:00:00.000Z","1995-07-23T00:00:00.000Z","1995-07-30T00:00:00.000Z","1995-08-06T00:00:00.000Z","1995-08-13T00:00:00.000Z","1995-08-20T00:00:00.000Z","1995-08-27T00:00:00.000Z","1995-09-03T00:00:00.000Z","1995-09-10T00:00:00.000Z","1995-09-17T00:00:00.000Z","1995-09-24T00:00:00.000Z","1995-10-01T00:00:00.000Z","1995-10-08T00:00:00.000Z","1995-10-15T00:00:00.000Z","1995-10-22T00:00:00.000Z","1995-10-29T00:00:00.000Z","1995-11-05T00:00:00.000Z","1995-11-12T00:00:00.000Z","1995-11-19T00:00:00.000Z","1995-11-26T00:00:00.000Z","1995-12-03T00:00:00.000Z","1995-12-10T00:00:00.000Z","1995-12-17T00:00:00.000Z","1995-12-24T00:00:00.000Z","1995-12-31T00:00:00.000Z"],
-        vec!["1996-01-07T00:00:00.000Z","1996-01-14T00:00:00.000Z","1996-01-21T00:00:00.000Z","1996-01-28T00:00:00.000Z","1996-02-04T00:00:00.000Z","1996-02-11T00:00:00.000Z","1996-02-18T00:00:00.000Z","1996-02-25T00:00:00.000Z","1996-03-03T00:00:00.000Z","1996-03-10T00:00:00.000Z","1996-03-17T00:00:00.000Z","1996-03-24T00:00:00.000Z","1996-03-31T00:00:00.000Z","1996-04-07T00:00:00.000Z","1996-04-14T00:00:00.000Z","1996-04-21T00:00:00.000Z","1996-04-28T00:00:00.000Z","1996-05-05T00:00:00.000Z","1996-05-12T00:00:00.000Z","1996-05-19T00:00:00.000Z","1996-05-26T00:00:00.000Z","1996-06-02T00:00:00.000Z","1996-06-09T00:00:00.000Z","1996-06-16T00:00:00.000Z","1996-06-23T00:00:00.000Z","1996-06-30T00:00:00.000Z","1996-07-07T00:00:00.000Z","1996-07-14T00:00:00.000Z","1996-07-21T00:00:00.000Z","1996-07-28T00:00:00.000Z","1996-08-04T00:00:00.000Z","1996-08-11T00:00:00.000Z","1996-08-18T00:00:00.000Z","1996-08-25T00:00:00.000Z","1996-09-01T00:00:00.000Z","1996-09-08T00:00:00.000Z","1996-09-15T00:00:00.000Z","1996-09-22T00:00:00.000Z","1996-09-29T00:00:00.000Z","1996-10-06T00:00:00.000Z","1996-10-13T00:00:00.000Z","1996-10-20T00:00:00.000Z","1996-10-27T00:00:00.000Z","1996-11-03T00:00:00.000Z","1996-11-10T00:00:00.000Z","1996-11-17T00:00:00.000Z","1996-11-24T00:00:00.000Z","1996-12-01T00:00:00.000Z","1996-12-08T00:00:00.000Z","1996-12-15T00:00:00.000Z","1996-12-22T00:00:00.000Z","1996-12-29T00:00:00.000Z"],
-        vec!["1997-01-05T00:00:00.000Z","1997-01-12T00:00:00.000Z","1997-01-19T00:00:00.000Z","1997-01-26T00:00:00.000Z","1997-02-02T00:00:00.000Z","1997-02-09T00:00:00.000Z","1997-02-16T00:00:00.000Z","1997-02-23T00:00:00.000Z","1997-03-02T00:00:00.000Z","1997-03-09T00:00:00.000Z","1997-03-16T00:00:00.000Z","1997-03-23T00:00:00.000Z","1997-03-30T00:00:00.000Z","1997-04-06T00:00:00.000Z","1997-04-13T00:00:00.000Z","1997-04-20T00:00:00.000Z","1997-04-27T00:00:00.000Z","1997-05-04T00:00:00.000Z","1997-05-11T00:00:00.000Z","1997-05-18T00:00:00.000Z","1997-05-25T00:00:00.000Z","1997-06-01T00:00:00.000Z","1997-06-08T00:00:00.000Z","1997-06-15T00:00:00.000Z","1997-06-22T00:00:00.000Z","1997-06-29T00:00:00.000Z","1997-07-06T00:00:00.000Z","1997-07-13T00:00:00.000Z","1997-07-20T00:00:00.000Z","1997-07-27T00:00:00.000Z","1997-08-03T00:00:00.000Z","1997-08-10T00:00:00.000Z","1997-08-17T00:00:00.000Z","1997-08-24T00:00:00.000Z","1997-08-31T00:00:00.000Z","1997-09-07T00:00:00.000Z","1997-09-14T00:00:00.000Z","1997-09-21T00:00:00.000Z","1997-09-28T00:00:00.000Z","1997-10-05T00:00:00.000Z","1997-10-12T00:00:00.000Z","1997-10-19T00:00:00.000Z","1997-10-26T00:00:00.000Z","1997-11-02T00:00:00.000Z","1997-11-09T00:00:00.000Z","1997-11-16T00:00:00.000Z","1997-11-23T00:00:00.000Z","1997-11-30T00:00:00.000Z","1997-12-07T00:00:00.000Z","1997-12-14T00:00:00.000Z","1997-12-21T00:00:00.000Z","1997-12-28T00:00:00.000Z"],
-        vec!["1998-01-04T00:00:00.000Z","1998-01-11T00:00:00.000Z","1998-01-18T00:00:00.000Z","1998-01-25T00:00:00.000Z","1998-02-01T00:00:00.000Z","1998-02-08T00:00:00.000Z","1998-02-15T00:00:00.000Z","1998-02-22T00:00:00.000Z","1998-03-01T00:00:00.000Z","1998-03-08T00:00:00.000Z","1998-03-15T00:00:00.000Z","1998-03-22T00:00:00.000Z","1998-03-29T00:00:00.000Z","1998-04-05T00:00:00.000Z","1998-04-12T00:00:00.000Z","1998-04-19T00:00:00.000Z","1998-04-26T00:00:00.000Z","1998-05-03T00:00:00.000Z","1998-05-10T00:00:00.000Z","1998-05-17T00:00:00.000Z","1998-05-24T00:00:00.000Z","1998-05-31T00:00:00.000Z","1998-06-07T00:00:00.000Z","1998-06-14T00:00:00.000Z","1998-06-21T00:00:00.000Z","1998-06-28T00:00:00.000Z","1998-07-05T00:00:00.000Z","1998-07-12T00:00:00.000Z","1998-07-19T00:00:00.000Z","1998-07-26T00:00:00.000Z","1998-08-02T00:00:00.000Z","1998-08-09T00:00:00.000Z","1998-08-16T00:00:00.000Z","1998-08-23T00:00:00.000Z","1998-08-30T00:00:00.000Z","1998-09-06T00:00:00.000Z","1998-09-13T00:00:00.000Z","1998-09-20T00:00:00.000Z","1998-09-27T00:00:00.000Z","1998-10-04T00:00:00.000Z","1998-10-11T00:00:00.000Z","1998-10-18T00:00:00.000Z","1998-10-25T00:00:00.000Z","1998-11-01T00:00:00.000Z","1998-11-08T00:00:00.000Z","1998-11-15T00:00:00.000Z","1998-11-22T00:00:00.000Z","1998-11-29T00:00:00.000Z","1998-12-06T00:00:00.000Z","1998-12-13T00:00:00.000Z","1998-12-20T00:00:00.000Z","1998-12-27T00:00:00.000Z"],
-        vec!["1999-01-03T00:00:00.000Z","1999-01-10T00:00:00.000Z","1999-01-17T00:00:00.000Z","1999-01-24T00:00:00.000Z","1999-01-31T00:00:00.000Z","1999-02-07T00:00:00.000Z","1999-02-14T00:00:00.000Z","1999-02-21T00:00:00.000Z","1999-02-28T00:00:00.000Z","1999-03-07T00:00:00.000Z","1999-03-14T00:00:00.000Z","1999-03-21T00:00:00.000Z","1999-03-28T00:00:00.000Z","1999-04-04T00:00:00.000Z","1999-04-11T00:00:00.000Z","1999-04-18T00:00:00.000Z","1999-04-25T00:00:00.000Z","1999-05-02T00:00:00.000Z","1999-05-09T00:00:00.000Z","1999-05-16T00:00:00.000Z","1999-05-23T00:00:00.000Z","1999-05-30T00:00:00.000Z","1999-06-06T00:00:00.000Z","1999-06-13T00:00:00.000Z","1999-06-20T00:00:00.000Z","1999-06-27T00:00:00.000Z","1999-07-04T00:00:00.000Z","1999-07-11T00:00:00.000Z","1999-07-18T00:00:00.000Z","1999-07-25T00:00:00.000Z","1999-08-01T00:00:00.000Z","1999-08-08T00:00:00.000Z","1999-08-15T00:00:00.000Z","1999-08-22T00:00:00.000Z","1999-08-29T00:00:00.000Z","1999-09-05T00:00:00.000Z","1999-09-12T00:00:00.000Z","1999-09-19T00:00:00.000Z","1999-09-26T00:00:00.000Z","1999-10-03T00:00:00.000Z","1999-10-10T00:00:00.000Z","1999-10-17T00:00:00.000Z","1999-10-24T00:00:00.000Z","1999-10-31T00:00:00.000Z","1999-11-07T00:00:00.000Z","1999-11-14T00:00:00.000Z","1999-11-21T00:00:00.000Z","1999-11-28T00:00:00.000Z","1999-12-05T00:00:00.000Z","1999-12-12T00:00:00.000Z","1999-12-19T00:00:00.000Z","1999-12-26T00:00:00.000Z"],
-        vec!["2000-01-02T00:00:00.000Z","2000-01-09T00:00:00.000Z","2000-01-16T00:00:00.000Z","2000-01-23T00:00:00.000Z","2000-01-30T00:00:00.000Z","2000-02-06T00:00:00.000Z","2000-02-13T00:00:00.000Z","2000-02-20T00:00:00.000Z","2000-02-27T00:00:00.000Z","2000-03-05T00:00:00.000Z","2000-03-12T00:00:00.000Z","2000-03-19T00:00:00.000Z","2000-03-26T00:00:00.000Z","2000-04-02T00:00:00.000Z","2000-04-09T00:00:00.000Z","2000-04-16T00:00:00.000Z","2000-04-23T00:00:00.000Z","2000-04-30T00:00:00.000Z","2000-05-07T00:00:00.000Z","2000-05-14T00:00:00.000Z","2000-05-21T00:00:00.000Z","2000-05-28T00:00:00.000Z","2000-06-04T00:00:00.000Z","2000-06-11T00:00:00.000Z","2000-06-18T00:00:00.000Z","2000-06-25T00:00:00.000Z","2000-07-02T00:00:00.000Z","2000-07-09T00:00:00.000Z","2000-07-16T00:00:00.000Z","2000-07-23T00:00:00.000Z","2000-07-30T00:00:00.000Z","2000-08-06T00:00:00.000Z","2000-08-13T00:00:00.000Z","2000-08-20T00:00:00.000Z","2000-08-27T00:00:00.000Z","2000-09-03T00:00:00.000Z","2000-09-10T00:00:00.000Z","2000-09-17T00:00:00.000Z","2000-09-24T00:00:00.000Z","2000-10-01T00:00:00.000Z","2000-10-08T00:00:00.000Z","2000-10-15T00:00:00.000Z","2000-10-22T00:00:00.000Z","2000-10-29T00:00:00.000Z","2000-11-05T00:00:00.000Z","2000-11-12T00:00:00.000Z","2000-11-19T00:00:00.000Z","2000-11-26T00:00:00.000Z","2000-12-03T00:00:00.000Z","2000-12-10T00:00:00.000Z","2000-12-17T00:00:00.000Z","2000-12-24T00:00:00.000Z","2000-12-31T00:00:00.000Z"],
-        vec!["2001-01-07T00:00:00.000Z","2001-01-14T00:00:00.000Z","2001-01-21T00:00:00.000Z","2001-01-28T00:00:00.000Z","2001-02-04T00:00:00.000Z","2001-02-11T00:00:00.000Z","2001-02-18T00:00:00.000Z","2001-02-25T00:00:00.000Z","2001-03-04T00:00:00.000Z","2001-03-11T00:00:00.000Z","2001-03-18T00:00:00.000Z","2001-03-25T00:00:00.000Z","2001-04-01T00:00:00.000Z","2001-04-08T00:00:00.000Z","2001-04-15T00:00:00.000Z","2001-04-22T00:00:00.000Z","2001-04-29T00:00:00.000Z","2001-05-06T00:00:00.000Z","2001-05-13T00:00:00.000Z","2001-05-20T00:00:00.000Z","2001-05-27T00:00:00.000Z","2001-06-03T00:00:00.000Z","2001-06-10T00:00:00.000Z","2001-06-17T00:00:00.000Z","2001-06-24T00:00:00.000Z","2001-07-01T00:00:00.000Z","2001-07-08T00:00:00.000Z","2001-07-15T00:00:00.000Z","2001-07-22T00:00:00.000Z","2001-07-29T00:00:00.000Z","2001-08-05T00:00:00.000Z","2001-08-12T00:00:00.000Z","2001-08-19T00:00:00.000Z","2001-08-26T00:00:00.000Z","2001-09-02T00:00:00.000Z","2001-09-09T00:00:00.000Z","2001-09-16T00:00:00.000Z","2001-09-23T00:00:00.000Z","2001-09-30T00:00:00.000Z","2001-10-07T00:00:00.000Z","2001-10-14T00:00:00.000Z","2001-10-21T00:00:00.000Z","2001-10-28T00:00:00.000Z","2001-11-04T00:00:00.000Z","2001-11-11T00:00:00.000Z","2001-11-18T00:00:00.000Z","2001-11-25T00:00:00.000Z","2001-12-02T00:00:00.000Z","2001-12-09T00:00:00.000Z","2001-12-16T00:00:00.000Z","2001-12-23T00:00:00.000Z","2001-12-30T00:00:00.000Z"],
-        vec!["2002-01-06T00:00:00.000Z","2002-01-13T00:00:00.000Z","2002-01-20T00:00:00.000Z","2002-01-27T00:00:00.000Z","2002-02-03T00:00:00.000Z","2002-02-10T00:00:00.000Z","2002-02-17T00:00:00.000Z","2002-02-24T00:00:00.000Z","2002-03-03T00:00:00.000Z","2002-03-10T00:00:00.000Z","2002-03-17T00:00:00.000Z","2002-03-24T00:00:00.000Z","2002-03-31T00:00:00.000Z","2002-04-07T00:00:00.000Z","2002-04-14T00:00:00.000Z","2002-04-21T00:00:00.000Z","2002-04-28T00:00:00.000Z","2002-05-05T00:00:00.000Z","2002-05-12T00:00:00.000Z","2002-05-19T00:00:00.000Z","2002-05-26T00:00:00.000Z","2002-06-02T00:00:00.000Z","2002-06-09T00:00:00.000Z","2002-06-16T00:00:00.000Z","2002-06-23T00:00:00.000Z","2002-06-30T00:00:00.000Z","2002-07-07T00:00:00.000Z","2002-07-14T00:00:00.000Z","2002-07-21T00:00:00.000Z","2002-07-28T00:00:00.000Z","2002-08-04T00:00:00.000Z","2002-08-11T00:00:00.000Z","2002-08-18T00:00:00.000Z","2002-08-25T00:00:00.000Z","2002-09-01T00:00:00.000Z","2002-09-08T00:00:00.000Z","2002-09-15T00:00:00.000Z","2002-09-22T00:00:00.000Z","2002-09-29T00:00:00.000Z","2002-10-06T00:00:00.000Z","2002-10-13T00:00:00.000Z","2002-10-20T00:00:00.000Z","2002-10-27T00:00:00.000Z","2002-11-03T00:00:00.000Z","2002-11-10T00:00:00.000Z","2002-11-17T00:00:00.000Z","2002-11-24T00:00:00.000Z","2002-12-01T00:00:00.000Z","2002-12-08T00:00:00.000Z","2002-12-15T00:00:00.000Z","2002-12-22T00:00:00.000Z","2002-12-29T00:00:00.000Z"],
-        vec!["2003-01-05T00:00:00.000Z","2003-01-12T00:00:00.000Z","2003-01-19T00:00:00.000Z","2003-01-26T00:00:00.000Z","2003-02-02T00:00:00.000Z","2003-02-09T00:00:00.000Z","2003-02-16T00:00:00.000Z","2003-02-23T00:00:00.000Z","2003-03-02T00:00:00.000Z","2003-03-09T00:00:00.000Z","2003-03-16T00:00:00.000Z","2003-03-23T00:00:00.000Z","2003-03-30T00:00:00.000Z","2003-04-06T00:00:00.000Z","2003-04-13T00:00:00.000Z","2003-04-20T00:00:00.000Z","2003-04-27T00:00:00.000Z","2003-05-04T00:00:00.000Z","2003-05-11T00:00:00.000Z","2003-05-18T00:00:00.000Z","2003-05-25T00:00:00.000Z","2003-06-01T00:00:00.000Z","2003-06-08T00:00:00.000Z","2003-06-15T00:00:00.000Z","2003-06-22T00:00:00.000Z","2003-06-29T00:00:00.000Z","2003-07-06T00:00:00.000Z","2003-07-13T00:00:00.000Z","2003-07-20T00:00:00.000Z","2003-07-27T00:00:00.000Z","2003-08-03T00:00:00.000Z","2003-08-10T00:00:00.000Z","2003-08-17T00:00:00.000Z","2003-08-24T00:00:00.000Z","2003-08-31T00:00:00.000Z","2003-09-07T00:00:00.000Z","2003-09-14T00:00:00.000Z","2003-09-21T00:00:00.000Z","2003-09-28T00:00:00.000Z","2003-10-05T00:00:00.000Z","2003-10-12T00:00:00.000Z","2003-10-19T00:00:00.000Z","2003-10-26T00:00:00.000Z","2003-11-02T00:00:00.000Z","2003-11-09T00:00:00.000Z","2003-11-16T00:00:00.000Z","2003-11-23T00:00:00.000Z","2003-11-30T00:00:00.000Z","2003-12-07T00:00:00.000Z","2003-12-14T00:00:00.000Z","2003-12-21T00:00:00.000Z","2003-12-28T00:00:00.000Z"],
-        vec!["2004-01-04T00:00:00.000Z","2004-01-11T00:00:00.000Z","2004-01-18T00:00:00.000Z","2004-01-25T00:00:00.000Z","2004-02-01T00:00:00.000Z","2004-02-08T00:00:00.000Z","2004-02-15T00:00:00.000Z","2004-02-22T00:00:00.000Z","2004-02-29T00:00:00.000Z","2004-03-07T00:00:00.000Z","2004-03-14T00:00:00.000Z","2004-03-21T00:00:00.000Z","2004-03-28T00:00:00.000Z","2004-04-04T00:00:00.000Z","2004-04-11T00:00:00.000Z","2004-04-18T00:00:00.000Z","2004-04-25T00:00:00.000Z","2004-05-02T00:00:00.000Z","2004-05-09T00:00:00.000Z","2004-05-16T00:00:00.000Z","2004-05-23T00:00:00.000Z","2004-05-30T00:00:00.000Z","2004-06-06T00:00:00.000Z","2004-06-13T00:00:00.000Z","2004-06-20T00:00:00.000Z","2004-06-27T00:00:00.000Z","2004-07-04T00:00:00.000Z","2004-07-11T00:00:00.000Z","2004-07-18T00:00:00.000Z","2004-07-25T00:00:00.000Z","2004-08-01T00:00:00.000Z","2004-08-08T00:00:00.000Z","2004-08-15T00:00:00.000Z","2004-08-22T00:00:00.000Z","2004-08-29T00:00:00.000Z","2004-09-05T00:00:00.000Z","2004-09-12T00:00:00.000Z","2004-09-19T00:00:00.000Z","2004-09-26T00:00:00.000Z","2004-10-03T00:00:00.000Z","2004-10-10T00:00:00.000Z","2004-10-17T00:00:00.000Z","2004-10-24T00:00:00.000Z","2004-10-31T00:00:00.000Z","2004-11-07T00:00:00.000Z","2004-11-14T00:00:00.000Z","2004-11-21T00:00:00.000Z","2004-11-28T00:00:00.000Z","2004-12-05T00:00:00.000Z","2004-12-12T00:00:00.000Z","2004-12-19T00:00:00.000Z","2004-12-26T00:00:00.000Z"],
-        vec!["2005-01-02T00:00:00.000Z","2005-01-09T00:00:00.000Z","2005-01-16T00:00:00.000Z","2005-01-23T00:00:00.000Z","2005-01-30T00:00:00.000Z","2005-02-06T00:00:00.000Z","2005-02-13T00:00:00.000Z","2005-02-20T00:00:00.000Z","2005-02-27T00:00:00.000Z","2005-03-06T00:00:00.000Z","2005-03-13T00:00:00.000Z","2005-03-20T00:00:00.000Z","2005-03-27T00:00:00.000Z","2005-04-03T00:00:00.000Z","2005-04-10T00:00:00.000Z","2005-04-17T00:00:00.000Z","2005-04-24T00:00:00.000Z","2005-05-01T00:00:00.000Z","2005-05-08T00:00:00.000Z","2005-05-15T00:00:00.000Z","2005-05-22T00:00:00.000Z","2005-05-29T00:00:00.000Z","2005-06-05T00:00:00.000Z","2005-06-12T00:00:00.000Z","2005-06-19T00:00:00.000Z","2005-06-26T00:00:00.000Z","2005-07-03T00:00:00.000Z","2005-07-10T00:00:00.000Z","2005-07-17T00:00:00.000Z","2005-07-24T00:00:00.000Z","2005-07-31T00:00:00.000Z","2005-08-07T00:00:00.000Z","2005-08-14T00:00:00.000Z","2005-08-21T00:00:00.000Z","2005-08-28T00:00:00.000Z","2005-09-04T00:00:00.000Z","2005-09-11T00:00:00.000Z","2005-09-18T00:00:00.000Z","2005-09-25T00:00:00.000Z","2005-10-02T00:00:00.000Z","2005-10-09T00:00:00.000Z","2005-10-16T00:00:00.000Z","2005-10-23T00:00:00.000Z","2005-10-30T00:00:00.000Z","2005-11-06T00:00:00.000Z","2005-11-13T00:00:00.000Z","2005-11-20T00:00:00.000Z","2005-11-27T00:00:00.000Z","2005-12-04T00:00:00.000Z","2005-12-11T00:00:00.000Z","2005-12-18T00:00:00.000Z","2005-12-25T00:00:00.000Z"],
-        vec!["2006-01-01T00:00:00.000Z","2006-01-08T00:00:00.000Z","2006-01-15T00:00:00.000Z","2006-01-22T00:00:00.000Z","2006-01-29T00:00:00.000Z","2006-02-05T00:00:00.000Z","2006-02-12T00:00:00.000Z","2006-02-19T00:00:00.000Z","2006-02-26T00:00:00.000Z","2006-03-05T00:00:00.000Z","2006-03-12T00:00:00.000Z","2006-03-19T00:00:00.000Z","2006-03-26T00:00:00.000Z","2006-04-02T00:00:00.000Z","2006-04-09T00:00:00.000Z","2006-04-16T00:00:00.000Z","2006-04-23T00:00:00.000Z","2006-04-30T00:00:00.000Z","2006-05-07T00:00:00.000Z","2006-05-14T00:00:00.000Z","2006-05-21T00:00:00.000Z","2006-05-28T00:00:00.000Z","2006-06-04T00:00:00.000Z","2006-06-11T00:00:00.000Z","2006-06-18T00:00:00.000Z","2006-06-25T00:00:00.000Z","2006-07-02T00:00:00.000Z","2006-07-09T00:00:00.000Z","2006-07-16T00:00:00.000Z","2006-07-23T00:00:00.000Z","2006-07-30T00:00:00.000Z","2006-08-06T00:00:00.000Z","2006-08-13T00:00:00.000Z","2006-08-20T00:00:00.000Z","2006-08-27T00:00:00.000Z","2006-09-03T00:00:00.000Z","2006-09-10T00:00:00.000Z","2006-09-17T00:00:00.000Z","2006-09-24T00:00:00.000Z","2006-10-01T00:00:00.000Z","2006-10-08T00:00:00.000Z","2006-10-15T00:00:00.000Z","2006-10-22T00:00:00.000Z","2006-10-29T00:00:00.000Z","2006-11-05T00:00:00.000Z","2006-11-12T00:00:00.000Z","2006-11-19T00:00:00.000Z","2006-11-26T00:00:00.000Z","2006-12-03T00:00:00.000Z","2006-12-10T00:00:00.000Z","2006-12-17T00:00:00.000Z","2006-12-24T00:00:00.000Z","2006-12-31T00:00:00.000Z"],
-        vec!["2007-01-07T00:00:00.000Z","2007-01-14T00:00:00.000Z","2007-01-21T00:00:00.000Z","2007-01-28T00:00:00.000Z","2007-02-04T00:00:00.000Z","2007-02-11T00:00:00.000Z","2007-02-18T00:00:00.000Z","2007-02-25T00:00:00.000Z","2007-03-04T00:00:00.000Z","2007-03-11T00:00:00.000Z","2007-03-18T00:00:00.000Z","2007-03-25T00:00:00.000Z","2007-04-01T00:00:00.000Z","2007-04-08T00:00:00.000Z","2007-04-15T00:00:00.000Z","2007-04-22T00:00:00.000Z","2007-04-29T00:00:00.000Z","2007-05-06T00:00:00.000Z","2007-05-13T00:00:00.000Z","2007-05-20T00:00:00.000Z","2007-05-27T00:00:00.000Z","2007-06-03T00:00:00.000Z","2007-06-10T00:00:00.000Z","2007-06-17T00:00:00.000Z","2007-06-24T00:00:00.000Z","2007-07-01T00:00:00.000Z","2007-07-08T00:00:00.000Z","2007-07-15T00:00:00.000Z","2007-07-22T00:00:00.000Z","2007-07-29T00:00:00.000Z","2007-08-05T00:00:00.000Z","2007-08-12T00:00:00.000Z","2007-08-19T00:00:00.000Z","2007-08-26T00:00:00.000Z","2007-09-02T00:00:00.000Z","2007-09-09T00:00:00.000Z","2007-09-16T00:00:00.000Z","2007-09-23T00:00:00.000Z","2007-09-30T00:00:00.000Z","2007-10-07T00:00:00.000Z","2007-10-14T00:00:00.000Z","2007-10-21T00:00:00.000Z","2007-10-28T00:00:00.000Z","2007-11-04T00:00:00.000Z","2007-11-11T00:00:00.000Z","2007-11-18T00:00:00.000Z","2007-11-25T00:00:00.000Z","2007-12-02T00:00:00.000Z","2007-12-09T00:00:00.000Z","2007-12-16T00:00:00.000Z","2007-12-23T00:00:00.000Z","2007-12-30T00:00:00.000Z"],
-        vec!["2008-01-06T00:00:00.000Z","2008-01-13T00:00:00.000Z","2008-01-20T00:00:00.000Z","2008-01-27T00:00:00.000Z","2008-02-03T00:00:00.000Z","2008-02-10T00:00:00.000Z","2008-02-17T00:00:00.000Z","2008-02-24T00:00:00.000Z","2008-03-02T00:00:00.000Z","2008-03-09T00:00:00.000Z","2008-03-16T00:00:00.000Z","2008-03-23T00:00:00.000Z","2008-03-30T00:00:00.000Z","2008-04-06T00:00:00.000Z","2008-04-13T00:00:00.000Z","2008-04-20T00:00:00.000Z","2008-04-27T00:00:00.000Z","2008-05-04T00:00:00.000Z","2008-05-11T00:00:00.000Z","2008-05-18T00:00:00.000Z","2008-05-25T00:00:00.000Z","2008-06-01T00:00:00.000Z","2008-06-08T00:00:00.000Z","2008-06-15T00:00:00.000Z","2008-06-22T00:00:00.000Z","2008-06-29T00:00:00.000Z","2008-07-06T00:00:00.000Z","2008-07-13T00:00:00.000Z","2008-07-20T00:00:00.000Z","2008-07-27T00:00:00.000Z","2008-08-03T00:00:00.000Z","2008-08-10T00:00:00.000Z","2008-08-17T00:00:00.000Z","2008-08-24T00:00:00.000Z","2008-08-31T00:00:00.000Z","2008-09-07T00:00:00.000Z","2008-09-14T00:00:00.000Z","2008-09-21T00:00:00.000Z","2008-09-28T00:00:00.000Z","2008-10-05T00:00:00.000Z","2008-10-12T00:00:00.000Z","2008-10-19T00:00:00.000Z","2008-10-26T00:00:00.000Z","2008-11-02T00:00:00.000Z","2008-11-09T00:00:00.000Z","2008-11-16T00:00:00.000Z","2008-11-23T00:00:00.000Z","2008-11-30T00:00:00.000Z","2008-12-07T00:00:00.000Z","2008-12-14T00:00:00.000Z","2008-12-21T00:00:00.000Z","2008-12-28T00:00:00.000Z"],
-        vec!["2009-01-04T00:00:00.000Z","2009-01-11T00:00:00.000Z","2009-01-18T00:00:00.000Z","2009-01-25T00:00:00.000Z","2009-02-01T00:00:00.000Z","2009-02-08T00:00:00.000Z","2009-02-15T00:00:00.000Z","2009-02-22T00:00:00.000Z","2009-03-01T00:00:00.000Z","2009-03-08T00:00:00.000Z","2009-03-15T00:00:00.000Z","2009-03-22T00:00:00.000Z","2009-03-29T00:00:00.000Z","2009-04-05T00:00:00.000Z","2009-04-12T00:00:00.000Z","2009-04-19T00:00:00.000Z","2009-04-26T00:00:00.000Z","2009-05-03T00:00:00.000Z","2009-05-10T00:00:00.000Z","2009-05-17T00:00:00.000Z","2009-05-24T00:00:00.000Z","2009-05-31T00:00:00.000Z","2009-06-07T00:00:00.000Z","2009-06-14T00:00:00.000Z","2009-06-21T00:00:00.000Z","2009-06-28T00:00:00.000Z","2009-07-05T00:00:00.000Z","2009-07-12T00:00:00.000Z","2009-07-19T00:00:00.000Z","2009-07-26T00:00:00.000Z","2009-08-02T00:00:00.000Z","2009-08-09T00:00:00.000Z","2009-08-16T00:00:00.000Z","2009-08-23T00:00:00.000Z","2009-08-30T00:00:00.000Z","2009-09-06T00:00:00.000Z","2009-09-13T00:00:00.000Z","2009-09-20T00:00:00.000Z","2009-09-27T00:00:00.000Z","2009-10-04T00:00:00.000Z","2009-10-11T00:00:00.000Z","2009-10-18T00:00:00.000Z","2009-10-25T00:00:00.000Z","2009-11-01T00:00:00.000Z","2009-11-08T00:00:00.000Z","2009-11-15T00:00:00.000Z","2009-11-22T00:00:00.000Z","2009-11-29T00:00:00.000Z","2009-12-06T00:00:00.000Z","2009-12-13T00:00:00.000Z","2009-12-20T00:00:00.000Z","2009-12-27T00:00:00.000Z"],
-        vec!["2010-01-03T00:00:00.000Z","2010-01-10T00:00:00.000Z","2010-01-17T00:00:00.000Z","2010-01-24T00:00:00.000Z","2010-01-31T00:00:00.000Z","2010-02-07T00:00:00.000Z","2010-02-14T00:00:00.000Z","2010-02-21T00:00:00.000Z","2010-02-28T00:00:00.000Z","2010-03-07T00:00:00.000Z","2010-03-14T00:00:00.000Z","2010-03-21T00:00:00.000Z","2010-03-28T00:00:00.000Z","2010-04-04T00:00:00.000Z","2010-04-11T00:00:00.000Z","2010-04-18T00:00:00.000Z","2010-04-25T00:00:00.000Z","2010-05-02T00:00:00.000Z","2010-05-09T00:00:00.000Z","2010-05-16T00:00:00.000Z","2010-05-23T00:00:00.000Z","2010-05-30T00:00:00.000Z","2010-06-06T00:00:00.000Z","2010-06-13T00:00:00.000Z","2010-06-20T00:00:00.000Z","2010-06-27T00:00:00.000Z","2010-07-04T00:00:00.000Z","2010-07-11T00:00:00.000Z","2010-07-18T00:00:00.000Z","2010-07-25T00:00:00.000Z","2010-08-01T00:00:00.000Z","2010-08-08T00:00:00.000Z","2010-08-15T00:00:00.000Z","2010-08-22T00:00:00.000Z","2010-08-29T00:00:00.000Z","2010-09-05T00:00:00.000Z","2010-09-12T00:00:00.000Z","2010-09-19T00:00:00.000Z","2010-09-26T00:00:00.000Z","2010-10-03T00:00:00.000Z","2010-10-10T00:00:00.000Z","2010-10-17T00:00:00.000Z","2010-10-24T00:00:00.000Z","2010-10-31T00:00:00.000Z","2010-11-07T00:00:00.000Z","2010-11-14T00:00:00.000Z","2010-11-21T00:00:00.000Z","2010-11-28T00:00:00.000Z","2010-12-05T00:00:00.000Z","2010-12-12T00:00:00.000Z","2010-12-19T00:00:00.000Z","2010-12-26T00:00:00.000Z"],
-        vec!["2011-01-02T00:00:00.000Z","2011-01-09T00:00:00.000Z","2011-01-16T00:00:00.000Z","2011-01-23T00:00:00.000Z","2011-01-30T00:00:00.000Z","2011-02-06T00:00:00.000Z","2011-02-13T00:00:00.000Z","2011-02-20T00:00:00.000Z","2011-02-27T00:00:00.000Z","2011-03-06T00:00:00.000Z","2011-03-13T00:00:00.000Z","2011-03-20T00:00:00.000Z","2011-03-27T00:00:00.000Z","2011-04-03T00:00:00.000Z","2011-04-10T00:00:00.000Z","2011-04-17T00:00:00.000Z","2011-04-24T00:00:00.000Z","2011-05-01T00:00:00.000Z","2011-05-08T00:00:00.000Z","2011-05-15T00:00:00.000Z","2011-05-22T00:00:00.000Z","2011-05-29T00:00:00.000Z","2011-06-05T00:00:00.000Z","2011-06-12T00:00:00.000Z","2011-06-19T00:00:00.000Z","2011-06-26T00:00:00.000Z","2011-07-03T00:00:00.000Z","2011-07-10T00:00:00.000Z","2011-07-17T00:00:00.000Z","2011-07-24T00:00:00.000Z","2011-07-31T00:00:00.000Z","2011-08-07T00:00:00.000Z","2011-08-14T00:00:00.000Z","2011-08-21T00:00:00.000Z","2011-08-28T00:00:00.000Z","2011-09-04T00:00:00.000Z","2011-09-11T00:00:00.000Z","2011-09-18T00:00:00.000Z","2011-09-25T00:00:00.000Z","2011-10-02T00:00:00.000Z","2011-10-09T00:00:00.000Z","2011-10-16T00:00:00.000Z","2011-10-23T00:00:00.000Z","2011-10-30T00:00:00.000Z","2011-11-06T00:00:00.000Z","2011-11-13T00:00:00.000Z","2011-11-20T00:00:00.000Z","2011-11-27T00:00:00.000Z","2011-12-04T00:00:00.000Z","2011-12-11T00:00:00.000Z","2011-12-18T00:00:00.000Z","2011-12-25T00:00:00.000Z"],
-        vec!["2012-01-01T00:00:00.000Z","2012-01-08T00:00:00.000Z","2012-01-15T00:00:00.000Z","2012-01-22T00:00:00.000Z","2012-01-29T00:00:00.000Z","2012-02-05T00:00:00.000Z","2012-02-12T00:00:00.000Z","2012-02-19T00:00:00.000Z","2012-02-26T00:00:00.000Z","2012-03-04T00:00:00.000Z","2012-03-11T00:00:00.000Z","2012-03-18T00:00:00.000Z","2012-03-25T00:00:00.000Z","2012-04-01T00:00:00.000Z","2012-04-08T00:00:00.000Z","2012-04-15T00:00:00.000Z","2012-04-22T00:00:00.000Z","2012-04-29T00:00:00.000Z","2012-05-06T00:00:00.000Z","2012-05-13T00:00:00.000Z","2012-05-20T00:00:00.000Z","2012-05-27T00:00:00.000Z","2012-06-03T00:00:00.000Z","2012-06-10T00:00:00.000Z","2012-06-17T00:00:00.000Z","2012-06-24T00:00:00.000Z","2012-07-01T00:00:00.000Z","2012-07-08T00:00:00.000Z","2012-07-15T00:00:00.000Z","2012-07-22T00:00:00.000Z","2012-07-29T00:00:00.000Z","2012-08-05T00:00:00.000Z","2012-08-12T00:00:00.000Z","2012-08-19T00:00:00.000Z","2012-08-26T00:00:00.000Z","2012-09-02T00:00:00.000Z","2012-09-09T00:00:00.000Z","2012-09-16T00:00:00.000Z","2012-09-23T00:00:00.000Z","2012-09-30T00:00:00.000Z","2012-10-07T00:00:00.000Z","2012-10-14T00:00:00.000Z","2012-10-21T00:00:00.000Z","2012-10-28T00:00:00.000Z","2012-11-04T00:00:00.000Z","2012-11-11T00:00:00.000Z","2012-11-18T00:00:00.000Z","2012-11-25T00:00:00.000Z","2012-12-02T00:00:00.000Z","2012-12-09T00:00:00.000Z","2012-12-16T00:00:00.000Z","2012-12-23T00:00:00.000Z","2012-12-30T00:00:00.000Z"],
-        vec!["2013-01-06T00:00:00.000Z","2013-01-13T00:00:00.000Z","2013-01-20T00:00:00.000Z","2013-01-27T00:00:00.000Z","2013-02-03T00:00:00.000Z","2013-02-10T00:00:00.000Z","2013-02-17T00:00:00.000Z","2013-02-24T00:00:00.000Z","2013-03-03T00:00:00.000Z","2013-03-10T00:00:00.000Z","2013-03-17T00:00:00.000Z","2013-03-24T00:00:00.000Z","2013-03-31T00:00:00.000Z","2013-04-07T00:00:00.000Z","2013-04-14T00:00:00.000Z","2013-04-21T00:00:00.000Z","2013-04-28T00:00:00.000Z","2013-05-05T00:00:00.000Z","2013-05-12T00:00:00.000Z","2013-05-19T00:00:00.000Z","2013-05-26T00:00:00.000Z","2013-06-02T00:00:00.000Z","2013-06-09T00:00:00.000Z","2013-06-16T00:00:00.000Z","2013-06-23T00:00:00.000Z","2013-06-30T00:00:00.000Z","2013-07-07T00:00:00.000Z","2013-07-14T00:00:00.000Z","2013-07-21T00:00:00.000Z","2013-07-28T00:00:00.000Z","2013-08-04T00:00:00.000Z","2013-08-11T00:00:00.000Z","2013-08-18T00:00:00.000Z","2013-08-25T00:00:00.000Z","2013-09-01T00:00:00.000Z","2013-09-08T00:00:00.000Z","2013-09-15T00:00:00.000Z","2013-09-22T00:00:00.000Z","2013-09-29T00:00:00.000Z","2013-10-06T00:00:00.000Z","2013-10-13T00:00:00.000Z","2013-10-20T00:00:00.000Z","2013-10-27T00:00:00.000Z","2013-11-03T00:00:00.000Z","2013-11-10T00:00:00.000Z","2013-11-17T00:00:00.000Z","2013-11-24T00:00:00.000Z","2013-12-01T00:00:00.000Z","2013-12-08T00:00:00.000Z","2013-12-15T00:00:00.000Z","2013-12-22T00:00:00.000Z","2013-12-29T00:00:00.000Z"],
-        vec!["2014-01-05T00:00:00.000Z","2014-01-12T00:00:00.000Z","2014-01-19T00:00:00.000Z","2014-01-26T00:00:00.000Z","2014-02-02T00:00:00.000Z","2014-02-09T00:00:00.000Z","2014-02-16T00:00:00.000Z","2014-02-23T00:00:00.000Z","2014-03-02T00:00:00.000Z","2014-03-09T00:00:00.000Z","2014-03-16T00:00:00.000Z","2014-03-23T00:00:00.000Z","2014-03-30T00:00:00.000Z","2014-04-06T00:00:00.000Z","2014-04-13T00:00:00.000Z","2014-04-20T00:00:00.000Z","2014-04-27T00:00:00.000Z","2014-05-04T00:00:00.000Z","2014-05-11T00:00:00.000Z","2014-05-18T00:00:00.000Z","2014-05-25T00:00:00.000Z","2014-06-01T00:00:00.000Z","2014-06-08T00:00:00.000Z","2014-06-15T00:00:00.000Z","2014-06-22T00:00:00.000Z","2014-06-29T00:00:00.000Z","2014-07-06T00:00:00.000Z","2014-07-13T00:00:00.000Z","2014-07-20T00:00:00.000Z","2014-07-27T00:00:00.000Z","2014-08-03T00:00:00.000Z","2014-08-10T00:00:00.000Z","2014-08-17T00:00:00.000Z","2014-08-24T00:00:00.000Z","2014-08-31T00:00:00.000Z","2014-09-07T00:00:00.000Z","2014-09-14T00:00:00.000Z","2014-09-21T00:00:00.000Z","2014-09-28T00:00:00.000Z","2014-10-05T00:00:00.000Z","2014-10-12T00:00:00.000Z","2014-10-19T00:00:00.000Z","2014-10-26T00:00:00.000Z","2014-11-02T00:00:00.000Z","2014-11-09T00:00:00.000Z","2014-11-16T00:00:00.000Z","2014-11-23T00:00:00.000Z","2014-11-30T00:00:00.000Z","2014-12-07T00:00:00.000Z","2014-12-14T00:00:00.000Z","2014-12-21T00:00:00.000Z","2014-12-28T00:00:00.000Z"],
-        vec!["2015-01-04T00:00:00.000Z","2015-01-11T00:00:00.000Z","2015-01-18T00:00:00.000Z","2015-01-25T00:00:00.000Z","2015-02-01T00:00:00.000Z","2015-02-08T00:00:00.000Z","2015-02-15T00:00:00.000Z","2015-02-22T00:00:00.000Z","2015-03-01T00:00:00.000Z","2015-03-08T00:00:00.000Z","2015-03-15T00:00:00.000Z","2015-03-22T00:00:00.000Z","2015-03-29T00:00:00.000Z","2015-04-05T00:00:00.000Z","2015-04-12T00:00:00.000Z","2015-04-19T00:00:00.000Z","2015-04-26T00:00:00.000Z","2015-05-03T00:00:00.000Z","2015-05-10T00:00:00.000Z","2015-05-17T00:00:00.000Z","2015-05-24T00:00:00.000Z","2015-05-31T00:00:00.000Z","2015-06-07T00:00:00.000Z","2015-06-14T00:00:00.000Z","2015-06-21T00:00:00.000Z","2015-06-28T00:00:00.000Z","2015-07-05T00:00:00.000Z","2015-07-12T00:00:00.000Z","2015-07-19T00:00:00.000Z","2015-07-26T00:00:00.000Z","2015-08-02T00:00:00.000Z","2015-08-09T00:00:00.000Z","2015-08-16T00:00:00.000Z","2015-08-23T00:00:00.000Z","2015-08-30T00:00:00.000Z","2015-09-06T00:00:00.000Z","2015-09-13T00:00:00.000Z","2015-09-20T00:00:00.000Z","2015-09-27T00:00:00.000Z","2015-10-04T00:00:00.000Z","2015-10-11T00:00:00.000Z","2015-10-18T00:00:00.000Z","2015-10-25T00:00:00.000Z","2015-11-01T00:00:00.000Z","2015-11-08T00:00:00.000Z","2015-11-15T00:00:00.000Z","2015-11-22T00:00:00.000Z","2015-11-29T00:00:00.000Z","2015-12-06T00:00:00.000Z","2015-12-13T00:00:00.000Z","2015-12-20T00:00:00.000Z","2015-12-27T00:00:00.000Z"],
-        vec!["2016-01-03T00:00:00.000Z","2016-01-10T00:00:00.000Z","2016-01-17T00:00:00.000Z","2016-01-24T00:00:00.000Z","2016-01-31T00:00:00.000Z","2016-02-07T00:00:00.000Z","2016-02-14T00:00:00.000Z","2016-02-21T00:00:00.000Z","2016-02-28T00:00:00.000Z","2016-03-06T00:00:00.000Z","2016-03-13T00:00:00.000Z","2016-03-20T00:00:00.000Z","2016-03-27T00:00:00.000Z","2016-04-03T00:00:00.000Z","2016-04-10T00:00:00.000Z","2016-04-17T00:00:00.000Z","2016-04-24T00:00:00.000Z","2016-05-01T00:00:00.000Z","2016-05-08T00:00:00.000Z","2016-05-15T00:00:00.000Z","2016-05-22T00:00:00.000Z","2016-05-29T00:00:00.000Z","2016-06-05T00:00:00.000Z","2016-06-12T00:00:00.000Z","2016-06-19T00:00:00.000Z","2016-06-26T00:00:00.000Z","2016-07-03T00:00:00.000Z","2016-07-10T00:00:00.000Z","2016-07-17T00:00:00.000Z","2016-07-24T00:00:00.000Z","2016-07-31T00:00:00.000Z","2016-08-07T00:00:00.000Z","2016-08-14T00:00:00.000Z","2016-08-21T00:00:00.000Z","2016-08-28T00:00:00.000Z","2016-09-04T00:00:00.000Z","2016-09-11T00:00:00.000Z","2016-09-18T00:00:00.000Z","2016-09-25T00:00:00.000Z","2016-10-02T00:00:00.000Z","2016-10-09T00:00:00.000Z","2016-10-16T00:00:00.000Z","2016-10-23T00:00:00.000Z","2016-10-30T00:00:00.000Z","2016-11-06T00:00:00.000Z","2016-11-13T00:00:00.000Z","2016-11-20T00:00:00.000Z","2016-11-27T00:00:00.000Z","2016-12-04T00:00:00.000Z","2016-12-11T00:00:00.000Z","2016-12-18T00:00:00.000Z","2016-12-25T00:00:00.000Z"],
-        vec!["2017-01-01T00:00:00.000Z","2017-01-08T00:00:00.000Z","2017-01-15T00:00:00.000Z","2017-01-22T00:00:00.000Z","2017-01-29T00:00:00.000Z","2017-02-05T00:00:00.000Z","2017-02-12T00:00:00.000Z","2017-02-19T00:00:00.000Z","2017-02-26T00:00:00.000Z","2017-03-05T00:00:00.000Z","2017-03-12T00:00:00.000Z","2017-03-19T00:00:00.000Z","2017-03-26T00:00:00.000Z","2017-04-02T00:00:00.000Z","2017-04-09T00:00:00.000Z","2017-04-16T00:00:00.000Z","2017-04-23T00:00:00.000Z","2017-04-30T00:00:00.000Z","2017-05-07T00:00:00.000Z","2017-05-14T00:00:00.000Z","2017-05-21T00:00:00.000Z","2017-05-28T00:00:00.000Z","2017-06-04T00:00:00.000Z","2017-06-11T00:00:00.000Z","2017-06-18T00:00:00.000Z","2017-06-25T00:00:00.000Z","2017-07-02T00:00:00.000Z","2017-07-09T00:00:00.000Z","2017-07-16T00:00:00.000Z","2017-07-23T00:00:00.000Z","2017-07-30T00:00:00.000Z","2017-08-06T00:00:00.000Z","2017-08-13T00:00:00.000Z","2017-08-20T00:00:00.000Z","2017-08-27T00:00:00.000Z","2017-09-03T00:00:00.000Z","2017-09-10T00:00:00.000Z","2017-09-17T00:00:00.000Z","2017-09-24T00:00:00.000Z","2017-10-01T00:00:00.000Z","2017-10-08T00:00:00.000Z","2017-10-15T00:00:00.000Z","2017-10-22T00:00:00.000Z","2017-10-29T00:00:00.000Z","2017-11-05T00:00:00.000Z","2017-11-12T00:00:00.000Z","2017-11-19T00:00:00.000Z","2017-11-26T00:00:00.000Z","2017-12-03T00:00:00.000Z","2017-12-10T00:00:00.000Z","2017-12-17T00:00:00.000Z","2017-12-24T00:00:00.000Z","2017-12-31T00:00:00.000Z"],
-        vec!["2018-01-07T00:00:00.000Z","2018-01-14T00:00:00.000Z","2018-01-21T00:00:00.000Z","2018-01-28T00:00:00.000Z","2018-02-04T00:00:00.000Z","2018-02-11T00:00:00.000Z","2018-02-18T00:00:00.000Z","2018-02-25T00:00:00.000Z","2018-03-04T00:00:00.000Z","2018-03-11T00:00:00.000Z","2018-03-18T00:00:00.000Z","2018-03-25T00:00:00.000Z","2018-04-01T00:00:00.000Z","2018-04-08T00:00:00.000Z","2018-04-15T00:00:00.000Z","2018-04-22T00:00:00.000Z","2018-04-29T00:00:00.000Z","2018-05-06T00:00:00.000Z","2018-05-13T00:00:00.000Z","2018-05-20T00:00:00.000Z","2018-05-27T00:00:00.000Z","2018-06-03T00:00:00.000Z","2018-06-10T00:00:00.000Z","2018-06-17T00:00:00.000Z","2018-06-24T00:00:00.000Z","2018-07-01T00:00:00.000Z","2018-07-08T00:00:00.000Z","2018-07-15T00:00:00.000Z","2018-07-22T00:00:00.000Z","2018-07-29T00:00:00.000Z","2018-08-05T00:00:00.000Z","2018-08-12T00:00:00.000Z","2018-08-19T00:00:00.000Z","2018-08-26T00:00:00.000Z","2018-09-02T00:00:00.000Z","2018-09-09T00:00:00.000Z","2018-09-16T00:00:00.000Z","2018-09-23T00:00:00.000Z","2018-09-30T00:00:00.000Z","2018-10-07T00:00:00.000Z","2018-10-14T00:00:00.000Z","2018-10-21T00:00:00.000Z","2018-10-28T00:00:00.000Z","2018-11-04T00:00:00.000Z","2018-11-11T00:00:00.000Z","2018-11-18T00:00:00.000Z","2018-11-25T00:00:00.000Z","2018-12-02T00:00:00.000Z","2018-12-09T00:00:00.000Z","2018-12-16T00:00:00.000Z","2018-12-23T00:00:00.000Z","2018-12-30T00:00:00.000Z"],
-        vec!["2019-01-06T00:00:00.000Z","2019-01-13T00:00:00.000Z","2019-01-20T00:00:00.000Z","2019-01-27T00:00:00.000Z","2019-02-03T00:00:00.000Z","2019-02-10T00:00:00.000Z","2019-02-17T00:00:00.000Z","2019-02-24T00:00:00.000Z","2019-03-03T00:00:00.000Z","2019-03-10T00:00:00.000Z","2019-03-17T00:00:00.000Z","2019-03-24T00:00:00.000Z","2019-03-31T00:00:00.000Z","2019-04-07T00:00:00.000Z","2019-04-14T00:00:00.000Z","2019-04-21T00:00:00.000Z","2019-04-28T00:00:00.000Z","2019-05-05T00:00:00.000Z","2019-05-12T00:00:00.000Z","2019-05-19T00:00:00.000Z","2019-05-26T00:00:00.000Z","2019-06-02T00:00:00.000Z","2019-06-09T00:00:00.000Z","2019-06-16T00:00:00.000Z","2019-06-23T00:00:00.000Z","2019-06-30T00:00:00.000Z","2019-07-07T00:00:00.000Z","2019-07-14T00:00:00.000Z","2019-07-21T00:00:00.000Z","2019-07-28T00:00:00.000Z","2019-08-04T00:00:00.000Z","2019-08-11T00:00:00.000Z","2019-08-18T00:00:00.000Z","2019-08-25T00:00:00.000Z","2019-09-01T00:00:00.000Z","2019-09-08T00:00:00.000Z","2019-09-15T00:00:00.000Z","2019-09-22T00:00:00.000Z","2019-09-29T00:00:00.000Z","2019-10-06T00:00:00.000Z","2019-10-13T00:00:00.000Z","2019-10-20T00:00:00.000Z","2019-10-27T00:00:00.000Z","2019-11-03T00:00:00.000Z","2019-11-10T00:00:00.000Z","2019-11-17T00:00:00.000Z","2019-11-24T00:00:00.000Z","2019-12-01T00:00:00.000Z","2019-12-08T00:00:00.000Z","2019-12-15T00:00:00.000Z","2019-12-22T00:00:00.000Z","2019-12-29T00:00:00.000Z"],
-        vec!["2020-01-05T00:00:00.000Z","2020-01-12T00:00:00.000Z","2020-01-19T00:00:00.000Z","2020-01-26T00:00:00.000Z","2020-02-02T00:00:00.000Z","2020-02-09T00:00:00.000Z","2020-02-16T00:00:00.000Z","2020-02-23T00:00:00.000Z","2020-03-01T00:00:00.000Z","2020-03-08T00:00:00.000Z","2020-03-15T00:00:00.000Z","2020-03-22T00:00:00.000Z","2020-03-29T00:00:00.000Z","2020-04-05T00:00:00.000Z","2020-04-12T00:00:00.000Z","2020-04-19T00:00:00.000Z","2020-04-26T00:00:00.000Z","2020-05-03T00:00:00.000Z","2020-05-10T00:00:00.000Z","2020-05-17T00:00:00.000Z","2020-05-24T00:00:00.000Z","2020-05-31T00:00:00.000Z","2020-06-07T00:00:00.000Z","2020-06-14T00:00:00.000Z","2020-06-21T00:00:00.000Z","2020-06-28T00:00:00.000Z","2020-07-05T00:00:00.000Z","2020-07-12T00:00:00.000Z","2020-07-19T00:00:00.000Z","2020-07-26T00:00:00.000Z","2020-08-02T00:00:00.000Z","2020-08-09T00:00:00.000Z","2020-08-16T00:00:00.000Z","2020-08-23T00:00:00.000Z","2020-08-30T00:00:00.000Z","2020-09-06T00:00:00.000Z","2020-09-13T00:00:00.000Z","2020-09-20T00:00:00.000Z","2020-09-27T00:00:00.000Z","2020-10-04T00:00:00.000Z","2020-10-11T00:00:00.000Z","2020-10-18T00:00:00.000Z","2020-10-25T00:00:00.000Z","2020-11-01T00:00:00.000Z","2020-11-08T00:00:00.000Z","2020-11-15T00:00:00.000Z","2020-11-22T00:00:00.000Z","2020-11-29T00:00:00.000Z","2020-12-06T00:00:00.000Z","2020-12-13T00:00:00.000Z","2020-12-20T00:00:00.000Z","2020-12-27T00:00:00.000Z"],
-        vec!["2021-01-03T00:00:00.000Z","2021-01-10T00:00:00.000Z","2021-01-17T00:00:00.000Z","2021-01-24T00:00:00.000Z","2021-01-31T00:00:00.000Z","2021-02-07T00:00:00.000Z","2021-02-14T00:00:00.000Z","2021-02-21T00:00:00.000Z","2021-02-28T00:00:00.000Z","2021-03-07T00:00:00.000Z","2021-03-14T00:00:00.000Z","2021-03-21T00:00:00.000Z","2021-03-28T00:00:00.000Z","2021-04-04T00:00:00.000Z","2021-04-11T00:00:00.000Z","2021-04-18T00:00:00.000Z","2021-04-25T00:00:00.000Z","2021-05-02T00:00:00.000Z","2021-05-09T00:00:00.000Z","2021-05-16T00:00:00.000Z","2021-05-23T00:00:00.000Z","2021-05-30T00:00:00.000Z","2021-06-06T00:00:00.000Z","2021-06-13T00:00:00.000Z","2021-06-20T00:00:00.000Z","2021-06-27T00:00:00.000Z","2021-07-04T00:00:00.000Z","2021-07-11T00:00:00.000Z","2021-07-18T00:00:00.000Z","2021-07-25T00:00:00.000Z","2021-08-01T00:00:00.000Z","2021-08-08T00:00:00.000Z","2021-08-15T00:00:00.000Z","2021-08-22T00:00:00.000Z","2021-08-29T00:00:00.000Z","2021-09-05T00:00:00.000Z","2021-09-12T00:00:00.000Z","2021-09-19T00:00:00.000Z","2021-09-26T00:00:00.000Z","2021-10-03T00:00:00.000Z","2021-10-10T00:00:00.000Z","2021-10-17T00:00:00.000Z","2021-10-24T00:00:00.000Z","2021-10-31T00:00:00.000Z","2021-11-07T00:00:00.000Z","2021-11-14T00:00:00.000Z","2021-11-21T00:00:00.000Z","2021-11-28T00:00:00.000Z","2021-12-05T00:00:00.000Z","2021-12-12T00:00:00.000Z","2021-12-19T00:00:00.000Z","2021-12-26T00:00:00.000Z"],
-        vec!["2022-01-02T00:00:00.000Z","2022-01-09T00:00:00.000Z","2022-01-16T00:00:00.000Z","2022-01-23T00:00:00.000Z","2022-01-30T00:00:00.000Z","2022-02-06T00:00:00.000Z","2022-02-13T00:00:00.000Z","2022-02-20T00:00:00.000Z","2022-02-27T00:00:00.000Z","2022-03-06T00:00:00.000Z","2022-03-13T00:00:00.000Z","2022-03-20T00:00:00.000Z","2022-03-27T00:00:00.000Z","2022-04-03T00:00:00.000Z","2022-04-10T00:00:00.000Z","2022-04-17T00:00:00.000Z","2022-04-24T00:00:00.000Z","2022-05-01T00:00:00.000Z","2022-05-08T00:00:00.000Z","2022-05-15T00:00:00.000Z","2022-05-22T00:00:00.000Z","2022-05-29T00:00:00.000Z","2022-06-05T00:00:00.000Z","2022-06-12T00:00:00.000Z","2022-06-19T00:00:00.000Z","2022-06-26T00:00:00.000Z","2022-07-03T00:00:00.000Z","2022-07-10T00:00:00.000Z","2022-07-17T00:00:00.000Z","2022-07-24T00:00:00.000Z"]
-    ];    
-
-    let timelattice = &timeseries[iter];
-    let outfiles = ["data/sla_adt_mean_1993.nc","data/sla_adt_mean_1994.nc","data/sla_adt_mean_1995.nc","data/sla_adt_mean_1996.nc","data/sla_adt_mean_1997.nc","data/sla_adt_mean_1998.nc","data/sla_adt_mean_1999.nc","data/sla_adt_mean_2000.nc","data/sla_adt_mean_2001.nc","data/sla_adt_mean_2002.nc","data/sla_adt_mean_2003.nc","data/sla_adt_mean_2004.nc","data/sla_adt_mean_2005.nc","data/sla_adt_mean_2006.nc","data/sla_adt_mean_2007.nc","data/sla_adt_mean_2008.nc","data/sla_adt_mean_2009.nc","data/sla_adt_mean_2010.nc","data/sla_adt_mean_2011.nc","data/sla_adt_mean_2012.nc","data/sla_adt_mean_2013.nc","data/sla_adt_mean_2014.nc","data/sla_adt_mean_2015.nc","data/sla_adt_mean_2016.nc","data/sla_adt_mean_2017.nc","data/sla_adt_mean_2018.nc","data/sla_adt_mean_2019.nc","data/sla_adt_mean_2020.nc","data/sla_adt_mean_2021.nc","data/sla_adt_mean_2022.nc"];
-
-    // caluclate intervals in days since 1993-01-01 for all timesteps
-    let mut timesteps = Vec::new();
-    let epoch = DateTime::parse_from_rfc3339("1993-01-01T00:00:00Z").unwrap();
-    for _i in 0..timelattice.len() {
-        let dt = DateTime::parse_from_rfc3339(timelattice[_i]).unwrap();
-        timesteps.push(dt.signed_duration_since(epoch).num_days());
-    }
-
-    // set up a new netcdf file to hold this period's averages
-    let mut outfile = netcdf::create(outfiles[iter])?;
-    outfile.add_dimension("latitude", 720)?;
-    outfile.add_dimension("longitude", 1440)?;
-    outfile.add_dimension("time", timelattice.len())?;
-
-    let mut timeidx = 0;
-
-    // vectors to hold interim results
-    let mut meanSLAs: Vec<Vec<Vec<f64>>> = vec![vec![vec![-999.9;1440];720];timelattice.len()];
-    let mut meanADTs: Vec<Vec<Vec<f64>>> = vec![vec![vec![-999.9;1440];720];timelattice.len()];
-    let mut meanUGOSAs: Vec<Vec<Vec<f64>>> = vec![vec![vec![-999.9;1440];720];timelattice.len()];
-    let mut meanVGOSAs: Vec<Vec<Vec<f64>>> = vec![vec![vec![-999.9;1440];720];timelattice.len()];
-    let mut meanUGOSs: Vec<Vec<Vec<f64>>> = vec![vec![vec![-999.9;1440];720];timelattice.len()];
-    let mut meanVGOSs: Vec<Vec<Vec<f64>>> = vec![vec![vec![-999.9;1440];720];timelattice.len()];
-    let mut meanTPAcorrection: Vec<f64> = vec![-999.9;timelattice.len()];
-    let mut slaCount: Vec<Vec<Vec<i32>>> = vec![vec![vec![0;1440];720];timelattice.len()];
-    let mut adtCount: Vec<Vec<Vec<i32>>> = vec![vec![vec![0;1440];720];timelattice.len()];
-    let mut ugosaCount: Vec<Vec<Vec<i32>>> = vec![vec![vec![0;1440];720];timelattice.len()];
-    let mut vgosaCount: Vec<Vec<Vec<i32>>> = vec![vec![vec![0;1440];720];timelattice.len()];
-    let mut ugosCount: Vec<Vec<Vec<i32>>> = vec![vec![vec![0;1440];720];timelattice.len()];
-    let mut vgosCount: Vec<Vec<Vec<i32>>> = vec![vec![vec![0;1440];720];timelattice.len()];
-    let mut TPAcorrectionCount: Vec<i32> = vec![0;timelattice.len()];
-
-    for d in timelattice {
-        // determine which daily files to average
-        let dates = timewindow(d, 7);
-
-        // load upstream data
-        for date in dates.iter(){
-            let f = netcdf::open(format!("data/dt_global_twosat_phy_l4_{}_vDT2021.nc", date))?;
-            let sla = &f.variable("sla").expect("Could not find variable 'sla'");
-            let adt = &f.variable("adt").expect("Could not find variable 'adt'");
-            let ugosa = &f.variable("ugosa").expect("Could not find variable 'ugosa'");
-            let vgosa = &f.variable("vgosa").expect("Could not find variable 'vgosa'");
-            let ugos = &f.variable("ugos").expect("Could not find variable 'ugos'");
-            let vgos = &f.variable("vgos").expect("Could not find variable 'vgos'");
-            let tpa = &f.variable("tpa_correction").expect("Could not find variable 'tpa_correction'");
-
-            let tpa_cxn = tpa.value::<i64, _>([0])?;
-            if tpa_cxn!=-2147483647 {
-                if meanTPAcorrection[timeidx] == -999.9 {
-                    // drop the fill value and start counting real values
-                    meanTPAcorrection[timeidx] = 0.0;
-                }
-                meanTPAcorrection[timeidx] += (tpa_cxn as f64) * 0.0001; // account for scale factor here
-                TPAcorrectionCount[timeidx] += 1;
-            }
+fn command_line() -> String {
+    // how this run was invoked, for the history of every file it writes
 
-            for lat in 0..720 {
-                for lon in 0..1440 {
-
-                    let anomaly = sla.value::<i64, _>([0, lat, lon])?;
-                    if anomaly!= -2147483647 {
-                        if meanSLAs[timeidx][lat][lon] == -999.9 {
-                            // drop the fill value and start counting real values
-                            meanSLAs[timeidx][lat][lon] = 0.0;
-                        }
-                        meanSLAs[timeidx][lat][lon] += (anomaly as f64) * 0.0001; // account for scale factor here
-                        slaCount[timeidx][lat][lon] += 1;
-                    }
+    std::env::args().collect::<Vec<String>>().join(" ")
+}
 
-                    let absolute = adt.value::<i64, _>([0, lat, lon])?;
-                    if absolute!= -2147483647 {
-                        if meanADTs[timeidx][lat][lon] == -999.9 {
-                            // drop the fill value and start counting real values
-                            meanADTs[timeidx][lat][lon] = 0.0;
-                        }
-                        meanADTs[timeidx][lat][lon] += (absolute as f64) * 0.0001; // account for scale factor here
-                        adtCount[timeidx][lat][lon] += 1;
-                    }
+fn workers(jobs: usize) -> Result<rayon::ThreadPool, netcdf::error::Error> {
+    rayon::ThreadPoolBuilder::new().num_threads(jobs).build().map_err(|e| format!("could not start {} workers: {}", jobs, e).into())
+}
 
-                    let u_anomaly = ugosa.value::<i64, _>([0, lat, lon])?;
-                    if u_anomaly!= -2147483647 {
-                        if meanUGOSAs[timeidx][lat][lon] == -999.9 {
-                            // drop the fill value and start counting real values
-                            meanUGOSAs[timeidx][lat][lon] = 0.0;
-                        }
-                        meanUGOSAs[timeidx][lat][lon] += (u_anomaly as f64) * 0.0001; // account for scale factor here
-                        ugosaCount[timeidx][lat][lon] += 1;
-                    }
+fn upstream_inputs(args: &RunArgs) -> Result<Inputs, netcdf::error::Error> {
+    // where to find the daily upstream file for each date
 
-                    let v_anomaly = vgosa.value::<i64, _>([0, lat, lon])?;
-                    if v_anomaly!= -2147483647 {
-                        if meanVGOSAs[timeidx][lat][lon] == -999.9 {
-                            // drop the fill value and start counting real values
-                            meanVGOSAs[timeidx][lat][lon] = 0.0;
-                        }
-                        meanVGOSAs[timeidx][lat][lon] += (v_anomaly as f64) * 0.0001; // account for scale factor here
-                        vgosaCount[timeidx][lat][lon] += 1;
-                    }
+    if args.discover {
+        Inputs::discover(&args.input_dir, &args.input_template, args.product_version.as_deref())
+    } else {
+        Inputs::from_template(&args.input_dir, &args.input_template, args.product_version.as_deref().unwrap_or(inputs::DEFAULT_VERSION))
+    }
+}
 
-                    let u_absolute = ugos.value::<i64, _>([0, lat, lon])?;
-                    if u_absolute!= -2147483647 {
-                        if meanUGOSs[timeidx][lat][lon] == -999.9 {
-                            // drop the fill value and start counting real values
-                            meanUGOSs[timeidx][lat][lon] = 0.0;
-                        }
-                        meanUGOSs[timeidx][lat][lon] += (u_absolute as f64) * 0.0001; // account for scale factor here
-                        ugosCount[timeidx][lat][lon] += 1;
-                    }
+fn upstream_variable<'f>(f: &'f netcdf::File, name: &str, path: &Path) -> Result<netcdf::Variable<'f>, netcdf::error::Error> {
+    // look up an upstream variable, complaining about the file it's missing from
 
-                    let v_absolute = vgos.value::<i64, _>([0, lat, lon])?;
-                    if v_absolute!= -2147483647 {
-                        if meanVGOSs[timeidx][lat][lon] == -999.9 {
-                            // drop the fill value and start counting real values
-                            meanVGOSs[timeidx][lat][lon] = 0.0;
-                        }
-                        meanVGOSs[timeidx][lat][lon] += (v_absolute as f64) * 0.0001; // account for scale factor here
-                        vgosCount[timeidx][lat][lon] += 1;
-                    }
-                }
-            }
-        }
+    match f.variable(name) {
+        Some(v) => Ok(v),
+        None => Err(format!("{}: could not find variable '{}'", path.display(), name).into())
+    }
+}
 
-        timeidx += 1;
+fn upstream_layout(f: &netcdf::File, name: &str, path: &Path) -> Result<Vec<(String, usize)>, netcdf::error::Error> {
+    // the dimensions of a daily upstream field after its leading, single-step time dimension
+
+    let var = upstream_variable(f, name, path)?;
+    let dims = var.dimensions();
+    match dims.first() {
+        Some(time) if time.name() == "time" && time.len() == 1 => Ok(dims[1..].iter().map(|d| (d.name(), d.len())).collect()),
+        _ => Err(format!("{}: '{}' is not a daily field with a leading time dimension", path.display(), name).into())
     }
+}
 
-    // anomaly means
-    let mut meansla = outfile.add_variable::<f64>("sla",&["time", "latitude", "longitude"])?;
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut msla = Vec::new();
-            for lon in 0..1440 {
-                if meanSLAs[time][lat][lon] != -999.9 {
-                    msla.push(meanSLAs[time][lat][lon] / (slaCount[time][lat][lon] as f64));
-                } else {
-                    msla.push(meanSLAs[time][lat][lon]);
-                }
+fn provenance(args: &RunArgs, upstream: &netcdf::File) -> Provenance {
+    // what to stamp on every output file of this run; with --discover and no version asked for,
+    // the version is whatever the upstream files say they are
+
+    let product_version = match (&args.product_version, args.discover) {
+        (Some(version), _) => version.clone(),
+        (None, false) => String::from(inputs::DEFAULT_VERSION),
+        (None, true) => attributes::text(upstream.attribute("product_version")).unwrap_or_else(|| String::from("unknown"))
+    };
+    Provenance{
+        created: nowstring(),
+        command_line: command_line(),
+        period: args.period,
+        window: args.window,
+        stride: args.stride.unwrap_or(args.window),
+        alignment: args.align,
+        product_version,
+        input_directory: args.input_dir.display().to_string(),
+        baseline: None,
+        tpa_correction: args.tpa_correction
+    }
+}
+
+fn selected_years(args: &RunArgs) -> Result<Vec<(i32, Vec<Window>)>, netcdf::error::Error> {
+    // windows picked out by the command line options, grouped by the calendar year they start in
+
+    let lattice = Lattice::new(lattice::parse_date(lattice::ANCHOR)?, args.period, args.window, args.stride.unwrap_or(args.window), args.align, lattice::parse_date(lattice::RECORD_START)?, args.end);
+    let mut years = Vec::new();
+    for year in lattice.years() {
+        if let Some((first, last)) = args.year {
+            if year < first || year > last {
+                continue;
             }
-            // write to file
-            meansla.put_values(&msla, (time, lat, ..));
+        }
+        let mut windows = lattice.year(year);
+        if let Some(start) = args.start {
+            windows.retain(|w| w.start >= start);
+        }
+        if !windows.is_empty() {
+            years.push((year, windows));
         }
     }
 
-    // anomaly counts
-    let mut sla_nobs = outfile.add_variable::<f64>("sla_nobs",&["time", "latitude", "longitude"])?;  // track how many non-fill-value observations the mean is calculated over
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut sla_nobsx = Vec::new();
-            for lon in 0..1440 {
-                sla_nobsx.push( slaCount[time][lat][lon]);
+    if years.is_empty() {
+        return Err("no complete windows fall within the requested years and dates".into());
+    }
+    Ok(years)
+}
+
+fn outfile_path(args: &RunArgs, year: i32, product: &str) -> PathBuf {
+    args.output_dir.join(format!("{}_{}.nc", output::stem(args.period, args.window, args.stride.unwrap_or(args.window), args.align, product), year))
+}
+
+fn inspect(args: &RunArgs) -> Result<(), netcdf::error::Error> {
+    // describe what an average run would do with these options
+
+    let inputs = upstream_inputs(args)?;
+    println!("{}", inputs.describe());
+    let epoch = lattice::parse_date(lattice::EPOCH)?;
+    let years = selected_years(args)?;
+    // what the outputs would declare, if there's a daily file to tell from
+    let layout = match years.iter().flat_map(|(_, windows)| windows.iter().flat_map(|w| w.dates())).find_map(|d| inputs.available(d)) {
+        Some(path) => {
+            let f = netcdf::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            Some(output::layout(&f, &provenance(args, &f), &accumulators(args, &f, &path, false)?, args.precision))
+        },
+        None => None
+    };
+    for (year, windows) in years {
+        let outfilename = outfile_path(args, year, "mean");
+        let progress = |path: &Path| layout.as_ref().and_then(|layout| output::progress(path, &windows, layout));
+        let status = match (progress(&outfilename), progress(&output::partial_path(&outfilename))) {
+            (Some(done), _) if done == windows.len() => String::from(" [complete]"),
+            (_, Some(done)) => format!(" [partial, {} of {} windows done]", done, windows.len()),
+            _ => String::new()
+        };
+        println!("{}: {} windows -> {}{}", year, windows.len(), outfilename.display(), status);
+        for window in windows.iter() {
+            println!("  {} (day {}): {} to {}", window.label, window.timestamp(epoch), window.start, window.end());
+            for date in window.dates() {
+                match inputs.path(date) {
+                    Ok(path) if path.exists() => println!("    {}", path.display()),
+                    Ok(path) => println!("    {} [missing]", path.display()),
+                    Err(_) => println!("    {} [missing]", date)
+                }
             }
-            // write to file
-            sla_nobs.put_values(&sla_nobsx, (time, lat, ..));
         }
     }
 
-    // absolute means
-    let mut meanadt = outfile.add_variable::<f64>("adt",&["time", "latitude", "longitude"])?;
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut madt = Vec::new();
-            for lon in 0..1440 {
-                if meanADTs[time][lat][lon] != -999.9 {
-                    madt.push(meanADTs[time][lat][lon] / (adtCount[time][lat][lon] as f64));
-                } else {
-                    madt.push(meanADTs[time][lat][lon]);
+    Ok(())
+}
+
+fn validate(args: &RunArgs) -> Result<(), netcdf::error::Error> {
+    // open every daily input a run would need and make sure it has the requested variables on a common grid
+
+    let inputs = upstream_inputs(args)?;
+    let mut grid: Option<Grid> = None;
+    let mut problems = 0;
+    let mut checked = 0;
+    for (_year, windows) in selected_years(args)? {
+        for window in windows.iter() {
+            for date in window.dates() {
+                checked += 1;
+                let path = match inputs.path(date) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        println!("{}", e);
+                        problems += 1;
+                        continue;
+                    }
+                };
+                let f = match netcdf::open(&path) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        println!("{}: {}", path.display(), e);
+                        problems += 1;
+                        continue;
+                    }
+                };
+                // everything has to be on the grid of the first file
+                let on_grid = match &grid {
+                    Some(grid) => grid.check(&f, &path),
+                    None => Grid::from_file(&f, &path).map(|g| grid = Some(g))
+                };
+                if let Err(e) = on_grid {
+                    println!("{}", e);
+                    problems += 1;
+                }
+                let mut needed: Vec<&str> = args.variables.iter().map(|v| v.as_str()).chain(args.derived.iter().flat_map(|d| {
+                    let (u, v) = derived::components(*d);
+                    [u, v]
+                })).collect();
+                needed.sort();
+                needed.dedup();
+                for v in needed {
+                    if f.variable(v).is_none() {
+                        println!("{}: missing variable '{}'", path.display(), v);
+                        problems += 1;
+                    }
                 }
             }
-            // write to file
-            meanadt.put_values(&madt, (time, lat, ..));
         }
     }
 
-    // absolute counts
-    let mut adt_nobs = outfile.add_variable::<f64>("adt_nobs",&["time", "latitude", "longitude"])?;  // track how many non-fill-value observations the mean is calculated over
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut adt_nobsx = Vec::new();
-            for lon in 0..1440 {
-                adt_nobsx.push( adtCount[time][lat][lon]);
-            }
-            // write to file
-            adt_nobs.put_values(&adt_nobsx, (time, lat, ..));
+    println!("checked {} daily files, found {} problems", checked, problems);
+    if problems > 0 {
+        return Err(format!("{} problems found in the daily inputs", problems).into());
+    }
+    Ok(())
+}
+
+fn storage(args: &RunArgs) -> Storage {
+    Storage{deflate: args.deflate, shuffle: args.shuffle, chunking: args.chunking, precision: args.precision}
+}
+
+fn accumulators(args: &RunArgs, f: &netcdf::File, path: &Path, departures: bool) -> Result<Vec<Accumulator>, netcdf::error::Error> {
+    // one accumulator per requested variable, laid out like the upstream field in f;
+    // departures from a climatology and drift corrected fields aren't packed integers, so they can't be summed as such
+
+    let mut accumulators = Vec::new();
+    for name in args.variables.iter() {
+        let var = upstream_variable(f, name, path)?;
+        let fill = output::fill_value(args.fill_value, &Packing::from_variable(&var)?);
+        let summation = match args.summation {
+            Summation::Packed if departures || (args.tpa_correction && output::CORRECTED.contains(&name.as_str())) || var.attribute("scale_factor").is_none() => Summation::Neumaier,
+            summation => summation
+        };
+        accumulators.push(Accumulator::new(name, upstream_layout(f, name, path)?, fill, &args.statistics, summation));
+    }
+    if args.shared_nobs {
+        let dims = accumulators[0].dims.clone();
+        accumulators.iter_mut().filter(|acc| acc.dims == dims).for_each(|acc| acc.nobs = String::from("nobs"));
+    }
+
+    // derived fields are in physical units and always keep their own counts, since their masks combine two variables';
+    // there's no spread or extreme of a direction
+    for derived in args.derived.iter() {
+        let (u, _) = derived::components(*derived);
+        let fill = output::fill_value(args.fill_value, &Packing::physical());
+        let statistics = if *derived == Derived::Direction {&[]} else {args.statistics.as_slice()};
+        let summation = if args.summation == Summation::Packed {Summation::Neumaier} else {args.summation};
+        let mut acc = Accumulator::new(derived::name(*derived), upstream_layout(f, u, path)?, fill, statistics, summation);
+        acc.derived = Some(*derived);
+        accumulators.push(acc);
+    }
+
+    Ok(accumulators)
+}
+
+fn read_field(f: &netcdf::File, name: &str, dims: &[(String, usize)], path: &Path, field: &mut Vec<f64>) -> Result<Packing, netcdf::error::Error> {
+    // read one day's packed field into field in one go, making sure it's laid out like the run started,
+    // and say how to unpack it
+
+    let layout = upstream_layout(f, name, path)?;
+    if layout != dims {
+        return Err(format!("{}: '{}' has dimensions {:?}, but the run started with {:?}", path.display(), name, layout, dims).into());
+    }
+    let var = upstream_variable(f, name, path)?;
+    field.resize(dims.iter().map(|(_, len)| len).product(), 0.0);
+    var.values_to(field, output::time_slice(0, dims.len())).map_err(|e| format!("{}: reading '{}': {}", path.display(), name, e))?;
+    Packing::from_variable(&var)
+}
+
+fn add_day(path: &Path, grid: &Grid, accumulators: &mut [Accumulator], slab: &mut Vec<f64>, baseline: Option<&Slot>, tpa_correction: bool) -> Result<(), netcdf::error::Error> {
+    // fold one daily file into every accumulator, drift corrected if asked, and as departures from the baseline if there is one
+
+    let f = netcdf::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    grid.check(&f, path)?;
+
+    // the TOPEX-A drift correction is a single number per day
+    let correction = if tpa_correction {
+        let var = upstream_variable(&f, "tpa_correction", path)?;
+        let packed = var.values::<f64, _>(..).map_err(|e| format!("{}: reading 'tpa_correction': {}", path.display(), e))?;
+        match packed.first().and_then(|p| Packing::from_variable(&var).ok()?.unpack(*p)) {
+            Some(correction) => Some(correction),
+            None => return Err(format!("{}: tpa_correction is missing, so sla and adt can't be corrected", path.display()).into())
         }
+    } else {
+        None
+    };
+
+    for acc in accumulators.iter_mut() {
+        let mut packing = match acc.derived {
+            Some(derived) => {
+                // computed from both components, each read like any other field
+                let (u, v) = derived::components(derived);
+                let (mut ufield, mut vfield) = (Vec::new(), Vec::new());
+                let upacking = read_field(&f, u, &acc.dims, path, &mut ufield)?;
+                let vpacking = read_field(&f, v, &acc.dims, path, &mut vfield)?;
+                slab.resize(acc.cells(), 0.0);
+                derived::compute(derived, &ufield, &upacking, &vfield, &vpacking, slab);
+                Packing::physical()
+            },
+            // pull the whole day's field in one read, then average in memory
+            None => read_field(&f, &acc.name, &acc.dims, path, slab)?
+        };
+        if let Some(correction) = correction.filter(|_| output::CORRECTED.contains(&acc.name.as_str())) {
+            slab.iter_mut().for_each(|x| *x = packing.unpack(*x).map(|v| v + correction).unwrap_or(f64::NAN));
+            packing = Packing::physical();
+        }
+        if let Some(slot) = baseline {
+            packing = slot.departures(&acc.name, &packing, slab)?;
+        }
+        acc.add_field(&packing, slab).map_err(|e| format!("{}: {}", path.display(), e))?;
     }
 
-    // u-anomaly means
-    let mut meanugosa = outfile.add_variable::<f64>("ugosa",&["time", "latitude", "longitude"])?;
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut mugosa = Vec::new();
-            for lon in 0..1440 {
-                if meanUGOSAs[time][lat][lon] != -999.9 {
-                    mugosa.push(meanUGOSAs[time][lat][lon] / (ugosaCount[time][lat][lon] as f64));
-                } else {
-                    mugosa.push(meanUGOSAs[time][lat][lon]);
-                }
+    Ok(())
+}
+
+fn climatology(args: &ClimatologyArgs) -> Result<(), netcdf::error::Error> {
+    // build a climatology over the reference years, or reuse it with --resume, then optionally
+    // average the selected years as departures from it
+
+    let run = &args.run;
+    if args.anomalies && run.derived.contains(&Derived::Direction) {
+        return Err("a direction has no departure from its climatology to average; leave direction out of --derived with --anomalies".into());
+    }
+    let climatology = Climatology{
+        path: run.output_dir.join(Climatology::file_name(args.climatology, args.reference)),
+        kind: args.climatology,
+        reference: args.reference,
+        smoothing: args.smoothing
+    };
+    // a running mean of means has no spread or extremes to carry, so --statistics only reaches the departures
+    if climatology.halfwidth() > 0 && !run.statistics.is_empty() && !args.anomalies {
+        return Err("a smoothed day-of-year climatology keeps only its means; drop --statistics, set --smoothing 1, or add --anomalies to have them for the departures".into());
+    }
+    if run.resume && climatology.path.exists() {
+        let variables: Vec<String> = run.variables.iter().cloned().chain(run.derived.iter().map(|d| String::from(derived::name(*d)))).collect();
+        climatology.check(&variables, run.tpa_correction)?;
+        println!("reusing the {}", climatology.describe());
+    } else {
+        build_climatology(run, &climatology)?;
+        println!("wrote the {}", climatology.describe());
+    }
+
+    if args.anomalies {
+        average_years(run, Some(&climatology))?;
+    }
+    Ok(())
+}
+
+fn build_climatology(args: &RunArgs, climatology: &Climatology) -> Result<(), netcdf::error::Error> {
+    // average every day of the reference years into its month or day of the year, then smooth
+    // day-of-year means over neighbouring days
+
+    let inputs = upstream_inputs(args)?;
+    let begin = lattice::parse_date(lattice::RECORD_START)?.max(NaiveDate::from_ymd_opt(climatology.reference.0, 1, 1).ok_or("reference period out of range")?);
+    let end = args.end.min(NaiveDate::from_ymd_opt(climatology.reference.1, 12, 31).ok_or("reference period out of range")?);
+    let dates: Vec<NaiveDate> = begin.iter_days().take_while(|d| *d <= end).collect();
+    if dates.is_empty() {
+        return Err(format!("the reference period {}-{} has no days in the record", climatology.reference.0, climatology.reference.1).into());
+    }
+
+    let missing: Vec<NaiveDate> = dates.iter().copied().filter(|d| inputs.available(*d).is_none()).collect();
+    if !missing.is_empty() && args.missing == MissingPolicy::Fail {
+        return Err(format!("{} daily inputs of the reference period missing, the first on {}; rerun with --missing skip to build the climatology from the days that are there", missing.len(), missing[0]).into());
+    }
+    let available: Vec<NaiveDate> = dates.into_iter().filter(|d| !missing.contains(d)).collect();
+    let groups = climatology::group(climatology.kind, &available);
+
+    let path = inputs.available(*available.first().ok_or("none of the daily inputs of the reference period could be found")?).ok_or("daily input disappeared")?;
+    let f = netcdf::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let grid = Grid::from_file(&f, &path)?;
+    let provenance = provenance(args, &f);
+    let storage = storage(args);
+    let mut accumulators = accumulators(args, &f, &path, false)?;
+    let mut slab: Vec<f64> = Vec::new();
+
+    // unsmoothed means go straight to the climatology file, or to a scratch file to be smoothed from
+    let partial = output::partial_path(&climatology.path);
+    let smoothed = climatology.halfwidth() > 0;
+    let raw = if smoothed {output::partial_path(&partial)} else {partial.clone()};
+    let raw_storage = Storage{deflate: 1, shuffle: true, chunking: Chunking::Map, precision: Precision::F64};
+    let streaming = output::streaming(&storage);
+    climatology.create(&raw, &f, &grid, &groups, 0, &accumulators, &provenance, if smoothed {&raw_storage} else {&streaming})?;
+
+    let pool = workers(args.jobs)?;
+    pool.install(|| -> Result<(), netcdf::error::Error> {
+        for (slot, dates) in groups.iter().enumerate() {
+            accumulators.iter_mut().for_each(|acc| acc.reset());
+            for date in dates.iter() {
+                let path = inputs.available(*date).ok_or_else(|| format!("daily input for {} disappeared", date))?;
+                add_day(&path, &grid, &mut accumulators, &mut slab, None, args.tpa_correction)?;
             }
-            // write to file
-            meanugosa.put_values(&mugosa, (time, lat, ..));
+            Climatology::write_slot(&raw, slot, &accumulators)?;
         }
+        Ok(())
+    })?;
+
+    if smoothed {
+        climatology.smooth(&raw, &partial, &f, &grid, &groups, &accumulators, &provenance, &streaming)?;
+        std::fs::remove_file(&raw).map_err(|e| format!("could not remove {}: {}", raw.display(), e))?;
+    }
+    // series chunks are only written once every slot is in
+    if storage.chunking == Chunking::Series {
+        let rechunked = output::partial_path(&partial);
+        let (halfwidth, declared) = if smoothed {(climatology.halfwidth(), Climatology::smoothed(&accumulators))} else {(0, accumulators)};
+        climatology.create(&rechunked, &f, &grid, &groups, halfwidth, &declared, &provenance, &storage)?;
+        output::rechunk(&partial, &rechunked)?;
+        std::fs::rename(&rechunked, &partial).map_err(|e| format!("could not move {} to {}: {}", rechunked.display(), partial.display(), e))?;
     }
+    std::fs::rename(&partial, &climatology.path).map_err(|e| format!("could not move {} to {}: {}", partial.display(), climatology.path.display(), e))?;
+    Ok(())
+}
 
-    // u-anomaly counts
-    let mut ugosa_nobs = outfile.add_variable::<f64>("ugosa_nobs",&["time", "latitude", "longitude"])?;  // track how many non-fill-value observations the mean is calculated over
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut ugosa_nobsx = Vec::new();
-            for lon in 0..1440 {
-                ugosa_nobsx.push( ugosaCount[time][lat][lon]);
-            }
-            // write to file
-            ugosa_nobs.put_values(&ugosa_nobsx, (time, lat, ..));
+fn averaged_stem(args: &AveragedArgs) -> String {
+    output::stem(args.period, args.window, args.stride.unwrap_or(args.window), args.align, if args.anomalies {"anomaly"} else {"mean"})
+}
+
+fn record_years(input_dir: &Path, stem: &str) -> Result<(i32, i32), netcdf::error::Error> {
+    // the first and last years with an averaged file in input_dir, however far the record was extended
+
+    let entries = std::fs::read_dir(input_dir).map_err(|e| format!("{}: {}", input_dir.display(), e))?;
+    let years: Vec<i32> = entries.filter_map(|entry| {
+        let name = entry.ok()?.file_name().into_string().ok()?;
+        let year = name.strip_prefix(stem)?.strip_prefix('_')?.strip_suffix(".nc")?;
+        if year.len() == 4 {year.parse::<i32>().ok()} else {None}
+    }).collect();
+    match (years.iter().min(), years.iter().max()) {
+        (Some(first), Some(last)) => Ok((*first, *last)),
+        _ => Err(format!("no averaged files named {}_<year>.nc in {}; run average first", stem, input_dir.display()).into())
+    }
+}
+
+fn averaged_files(input_dir: &Path, stem: &str, (first, last): (i32, i32)) -> Result<Vec<PathBuf>, netcdf::error::Error> {
+    // the yearly files of a finished averaging run
+
+    let paths: Vec<PathBuf> = (first..=last).map(|year| input_dir.join(format!("{}_{}.nc", stem, year))).collect();
+    let absent: Vec<String> = paths.iter().filter(|p| !p.exists()).map(|p| p.display().to_string()).collect();
+    if !absent.is_empty() {
+        return Err(format!("{} averaged files missing, the first {}; run average for those years first", absent.len(), absent[0]).into());
+    }
+    Ok(paths)
+}
+
+fn window_times(f: &netcdf::File, path: &Path) -> Result<Vec<f64>, netcdf::error::Error> {
+    // labels of an averaged file's windows, checked to be on the axis every output uses
+
+    let units = format!("days since {} 00:00:00", lattice::EPOCH);
+    let time = upstream_variable(f, "time", path)?;
+    let declared = attributes::text(time.attribute("units"));
+    if declared.as_deref() != Some(units.as_str()) {
+        return Err(format!("{}: time isn't in {}", path.display(), units).into());
+    }
+    time.values::<f64, _>(..).map_err(|e| format!("{}: reading 'time': {}", path.display(), e).into())
+}
+
+fn count_name(f: &netcdf::File, name: &str) -> String {
+    // the observation count of an averaged variable, its own or one shared with --shared-nobs
+
+    if f.variable(&format!("{}_nobs", name)).is_some() {format!("{}_nobs", name)} else {String::from("nobs")}
+}
+
+fn trend(args: &TrendArgs) -> Result<(), netcdf::error::Error> {
+    // fit trends to every cell of a run of averaged yearly files, reading each window's maps once
+
+    let stem = averaged_stem(&args.averaged);
+    let (first, last) = match args.year {
+        Some(years) => years,
+        None => record_years(&args.input_dir, &stem)?
+    };
+    let paths = averaged_files(&args.input_dir, &stem, (first, last))?;
+
+    // every window's time, all on the same axis, so the fits can be referred to the middle of the record
+    let epoch = lattice::parse_date(lattice::EPOCH)?;
+    let mut times = Vec::new();
+    for path in paths.iter() {
+        let f = netcdf::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        times.push(window_times(&f, path)?);
+    }
+    let (earliest, latest) = match (times.iter().flatten().copied().reduce(f64::min), times.iter().flatten().copied().reduce(f64::max)) {
+        (Some(earliest), Some(latest)) => (earliest, latest),
+        _ => return Err("the averaged files have no windows to fit".into())
+    };
+    let reference = trend::reference_date(epoch, earliest, latest);
+    let midpoint = reference.signed_duration_since(epoch).num_days() as f64;
+
+    // the fits are laid out like the first file's means, and only make sense for lengths
+    let f = netcdf::open(&paths[0]).map_err(|e| format!("{}: {}", paths[0].display(), e))?;
+    let grid = Grid::from_file(&f, &paths[0])?;
+    let mut regressions = Vec::new();
+    for name in args.variables.iter() {
+        let var = upstream_variable(&f, name, &paths[0])?;
+        let dims = var.dimensions();
+        if dims.first().map(|d| d.name()).as_deref() != Some("time") {
+            return Err(format!("{}: '{}' doesn't have a leading time dimension", paths[0].display(), name).into());
         }
+        let units = attributes::text(var.attribute("units")).unwrap_or_default();
+        let scale = trend::millimetres(&units).ok_or_else(|| format!("{}: '{}' is in '{}', but trends are only fitted to lengths", paths[0].display(), name, units))?;
+        regressions.push(Regression::new(name, &count_name(&f, name), dims[1..].iter().map(|d| (d.name(), d.len())).collect(), scale));
     }
 
-    // u-absolute means
-    let mut meanugos = outfile.add_variable::<f64>("ugos",&["time", "latitude", "longitude"])?;
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut mugos = Vec::new();
-            for lon in 0..1440 {
-                if meanUGOSs[time][lat][lon] != -999.9 {
-                    mugos.push(meanUGOSs[time][lat][lon] / (ugosCount[time][lat][lon] as f64));
-                } else {
-                    mugos.push(meanUGOSs[time][lat][lon]);
+    let pool = workers(args.jobs)?;
+    let mut field: Vec<f64> = Vec::new();
+    let mut counts: Vec<i32> = Vec::new();
+    pool.install(|| -> Result<(), netcdf::error::Error> {
+        for (path, time) in paths.iter().zip(times.iter()) {
+            let f = netcdf::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            grid.check(&f, path)?;
+            for reg in regressions.iter_mut() {
+                let var = upstream_variable(&f, &reg.name, path)?;
+                let nobs = upstream_variable(&f, &reg.nobs, path)?;
+                let packing = Packing::from_variable(&var)?;
+                field.resize(reg.cells(), 0.0);
+                counts.resize(reg.cells(), 0);
+                for (timeidx, t) in time.iter().enumerate() {
+                    var.values_to(&mut field, output::time_slice(timeidx, reg.dims.len())).map_err(|e| format!("{}: reading '{}': {}", path.display(), reg.name, e))?;
+                    nobs.values_to(&mut counts, output::time_slice(timeidx, reg.dims.len())).map_err(|e| format!("{}: reading '{}': {}", path.display(), reg.nobs, e))?;
+                    reg.add_field((t - midpoint) / trend::DAYS_PER_YEAR, &packing, &field, &counts);
                 }
             }
-            // write to file
-            meanugos.put_values(&mugos, (time, lat, ..));
         }
-    }
+        Ok(())
+    })?;
+
+    let outfilename = args.output_dir.join(format!("{}_trend_{}-{}.nc", stem, first, last));
+    let partial = output::partial_path(&outfilename);
+    let names: Vec<String> = paths.iter().map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| p.display().to_string())).collect();
+    let storage = Storage{deflate: args.deflate, shuffle: true, chunking: Chunking::Map, precision: Precision::F32};
+    pool.install(|| trend::write(&partial, &f, &grid, &regressions, args.quadratic, args.min_windows, reference, &names, &nowstring(), &command_line(), &storage))?;
+    std::fs::rename(&partial, &outfilename).map_err(|e| format!("could not move {} to {}: {}", partial.display(), outfilename.display(), e))?;
+    println!("wrote {}", outfilename.display());
+    Ok(())
+}
 
-    // u-absolute counts
-    let mut ugos_nobs = outfile.add_variable::<f64>("ugos_nobs",&["time", "latitude", "longitude"])?;  // track how many non-fill-value observations the mean is calculated over
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut ugos_nobsx = Vec::new();
-            for lon in 0..1440 {
-                ugos_nobsx.push( ugosCount[time][lat][lon]);
-            }
-            // write to file
-            ugos_nobs.put_values(&ugos_nobsx, (time, lat, ..));
+fn gmsl(args: &GmslArgs) -> Result<(), netcdf::error::Error> {
+    // area weighted means of every window of a run of averaged yearly files, as NetCDF and CSV time series
+
+    let stem = averaged_stem(&args.averaged);
+    let (first, last) = match args.year {
+        Some(years) => years,
+        None => record_years(&args.input_dir, &stem)?
+    };
+    let paths = averaged_files(&args.input_dir, &stem, (first, last))?;
+
+    let f = netcdf::open(&paths[0]).map_err(|e| format!("{}: {}", paths[0].display(), e))?;
+    let grid = Grid::from_file(&f, &paths[0])?;
+    let mut series = Vec::new();
+    for name in args.variables.iter() {
+        let var = upstream_variable(&f, name, &paths[0])?;
+        let dims: Vec<String> = var.dimensions().iter().map(|d| d.name()).collect();
+        if dims != ["time", "latitude", "longitude"] {
+            return Err(format!("{}: '{}' is laid out as {:?}, but area means need (time, latitude, longitude)", paths[0].display(), name, dims).into());
         }
+        series.push(Series{name: name.clone(), long_name: attributes::text(var.attribute("long_name")).unwrap_or_else(|| name.clone()), units: attributes::text(var.attribute("units")), values: Vec::new()});
     }
-
-    // v-anomaly means
-    let mut meanvgosa = outfile.add_variable::<f64>("vgosa",&["time", "latitude", "longitude"])?;
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut mvgosa = Vec::new();
-            for lon in 0..1440 {
-                if meanVGOSAs[time][lat][lon] != -999.9 {
-                    mvgosa.push(meanVGOSAs[time][lat][lon] / (vgosaCount[time][lat][lon] as f64));
-                } else {
-                    mvgosa.push(meanVGOSAs[time][lat][lon]);
+    let carried = attributes::text(f.attribute("tpa_correction"));
+    let correction = match (args.tpa_correction, carried) {
+        (true, Some(carried)) if carried == output::TPA_APPLIED => return Err(format!("{} was already averaged with --tpa-correction; leave it off here", paths[0].display()).into()),
+        (true, _) => {
+            upstream_variable(&f, "tpa_correction", &paths[0])?;
+            String::from("each window's mean tpa_correction added to the sla and adt means")
+        },
+        (false, carried) => carried.unwrap_or_else(|| String::from(output::TPA_NOT_APPLIED))
+    };
+    drop(f);
+
+    let mut times = Vec::new();
+    let mut bounds: Option<Vec<f64>> = Some(Vec::new());
+    let mut field: Vec<f64> = Vec::new();
+    let mut counts: Vec<i32> = Vec::new();
+    for path in paths.iter() {
+        let f = netcdf::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        grid.check(&f, path)?;
+        let time = window_times(&f, path)?;
+        // older files have no time_bnds, in which case the series doesn't either
+        bounds = match (bounds, f.variable("time_bnds")) {
+            (Some(mut bounds), Some(var)) => {
+                bounds.extend(var.values::<f64, _>(..).map_err(|e| format!("{}: reading 'time_bnds': {}", path.display(), e))?);
+                Some(bounds)
+            },
+            _ => None
+        };
+
+        // the correction is a single number per window, averaged like any other variable
+        let corrections: Vec<Option<f64>> = if args.tpa_correction {
+            let var = upstream_variable(&f, "tpa_correction", path)?;
+            let nobs = upstream_variable(&f, &count_name(&f, "tpa_correction"), path)?;
+            let packing = Packing::from_variable(&var)?;
+            let means = var.values::<f64, _>(..).map_err(|e| format!("{}: reading 'tpa_correction': {}", path.display(), e))?;
+            let counts = nobs.values::<i32, _>(..).map_err(|e| format!("{}: reading tpa_correction's count: {}", path.display(), e))?;
+            means.iter().zip(counts.iter()).map(|(m, c)| if *c > 0 {packing.unpack(*m)} else {None}).collect()
+        } else {
+            vec![Some(0.0); time.len()]
+        };
+
+        for s in series.iter_mut() {
+            let var = upstream_variable(&f, &s.name, path)?;
+            let nobs = upstream_variable(&f, &count_name(&f, &s.name), path)?;
+            let packing = Packing::from_variable(&var)?;
+            field.resize(grid.latitude.len() * grid.longitude.len(), 0.0);
+            counts.resize(field.len(), 0);
+            for (timeidx, correction) in corrections.iter().enumerate() {
+                var.values_to(&mut field, output::time_slice(timeidx, 2)).map_err(|e| format!("{}: reading '{}': {}", path.display(), s.name, e))?;
+                nobs.values_to(&mut counts, output::time_slice(timeidx, 2)).map_err(|e| format!("{}: reading the count of '{}': {}", path.display(), s.name, e))?;
+                let mut means = gmsl::weighted_means(&grid.latitude, &packing, &field, &counts);
+                if output::CORRECTED.contains(&s.name.as_str()) {
+                    means.iter_mut().for_each(|m| *m = m.zip(*correction).map(|(m, c)| m + c));
                 }
+                s.values.push(means);
             }
-            // write to file
-            meanvgosa.put_values(&mvgosa, (time, lat, ..));
         }
+        times.extend(time);
     }
 
-    // v-anomaly counts
-    let mut vgosa_nobs = outfile.add_variable::<f64>("vgosa_nobs",&["time", "latitude", "longitude"])?;  // track how many non-fill-value observations the mean is calculated over
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut vgosa_nobsx = Vec::new();
-            for lon in 0..1440 {
-                vgosa_nobsx.push( vgosaCount[time][lat][lon]);
-            }
-            // write to file
-            vgosa_nobs.put_values(&vgosa_nobsx, (time, lat, ..));
+    let f = netcdf::open(&paths[0]).map_err(|e| format!("{}: {}", paths[0].display(), e))?;
+    let names: Vec<String> = paths.iter().map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| p.display().to_string())).collect();
+    let outfilename = args.output_dir.join(format!("{}_gmsl_{}-{}.nc", stem, first, last));
+    let partial = output::partial_path(&outfilename);
+    gmsl::write_netcdf(&partial, &f, &times, bounds.as_deref(), &series, &correction, &names, &nowstring(), &command_line())?;
+    std::fs::rename(&partial, &outfilename).map_err(|e| format!("could not move {} to {}: {}", partial.display(), outfilename.display(), e))?;
+    let csv = outfilename.with_extension("csv");
+    gmsl::write_csv(&csv, lattice::parse_date(lattice::EPOCH)?, &times, &series)?;
+    println!("wrote {} and {}", outfilename.display(), csv.display());
+    Ok(())
+}
+
+fn average(args: &RunArgs) -> Result<(), netcdf::error::Error> {
+    average_years(args, None)
+}
+
+fn average_years(args: &RunArgs, baseline: Option<&Climatology>) -> Result<(), netcdf::error::Error> {
+    // produce one output file of window means per selected year, several years at a time,
+    // of departures from a climatology if there is one
+
+    let inputs = upstream_inputs(args)?;
+    let years = selected_years(args)?;
+    // every year is put on the grid of the first daily file the run can find
+    let path = years.iter().flat_map(|(_, windows)| windows.iter().flat_map(|w| w.dates())).find_map(|d| inputs.available(d)).ok_or("none of the requested daily inputs could be found")?;
+    let f = netcdf::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let grid = Grid::from_file(&f, &path)?;
+    let mut provenance = provenance(args, &f);
+    provenance.baseline = baseline.map(|c| c.describe());
+    let storage = storage(args);
+    let layout = output::layout(&f, &provenance, &accumulators(args, &f, &path, baseline.is_some())?, storage.precision);
+    drop(f);
+
+    // find out up front which daily files are absent across every year still to do,
+    // so a run that can't tolerate gaps fails before any year starts work
+    let missing: Vec<Vec<NaiveDate>> = years.iter().map(|(year, windows)| {
+        let outfilename = outfile_path(args, *year, if baseline.is_some() {"anomaly"} else {"mean"});
+        if complete(args, &outfilename, windows, &layout) {
+            return Vec::new();
         }
+        windows.iter().flat_map(|w| w.dates()).filter(|d| inputs.available(*d).is_none()).collect()
+    }).collect();
+    let absent: Vec<String> = missing.iter().flatten().map(|d| d.to_string()).collect();
+    if !absent.is_empty() && args.missing == MissingPolicy::Fail {
+        return Err(format!("{} daily inputs missing ({}); rerun with --missing skip to average the days that are there", absent.len(), absent.join(", ")).into());
     }
 
-    // v-absolute means
-    let mut meanvgos = outfile.add_variable::<f64>("vgos",&["time", "latitude", "longitude"])?;
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut mvgos = Vec::new();
-            for lon in 0..1440 {
-                if meanVGOSs[time][lat][lon] != -999.9 {
-                    mvgos.push(meanVGOSs[time][lat][lon] / (vgosCount[time][lat][lon] as f64));
-                } else {
-                    mvgos.push(meanVGOSs[time][lat][lon]);
-                }
+    let pool = workers(args.jobs)?;
+
+    // a failed year doesn't stop the others; report them all at the end
+    let results: Vec<(i32, Result<(), netcdf::error::Error>)> = pool.install(|| {
+        years.par_iter().zip(missing.par_iter()).map(|((year, windows), missing)| {
+            let outfilename = outfile_path(args, *year, if baseline.is_some() {"anomaly"} else {"mean"});
+            let result = average_year(args, &inputs, &grid, &provenance, &layout, &storage, baseline, &outfilename, windows, missing);
+            match result {
+                Ok(0) => println!("{}: {} is already complete", year, outfilename.display()),
+                Ok(computed) => println!("{}: wrote {} windows to {}", year, computed, outfilename.display()),
+                Err(_) => ()
             }
-            // write to file
-            meanvgos.put_values(&mvgos, (time, lat, ..));
+            (*year, result.map(|_| ()))
+        }).collect()
+    });
+
+    let mut failed = 0;
+    for (year, result) in results {
+        if let Err(e) = result {
+            eprintln!("{}: {}", year, e);
+            failed += 1;
         }
     }
+    if failed > 0 {
+        return Err(format!("{} of {} years failed", failed, years.len()).into());
+    }
+    Ok(())
+}
 
-    // v-absolute counts
-    let mut vgos_nobs = outfile.add_variable::<f64>("vgos_nobs",&["time", "latitude", "longitude"])?;  // track how many non-fill-value observations the mean is calculated over
-    for time in 0..timeidx{
-        for lat in 0..720 {
-            let mut vgos_nobsx = Vec::new();
-            for lon in 0..1440 {
-                vgos_nobsx.push( vgosCount[time][lat][lon]);
-            }
-            // write to file
-            vgos_nobs.put_values(&vgos_nobsx, (time, lat, ..));
-        }
+fn complete(args: &RunArgs, outfilename: &Path, timelattice: &[Window], layout: &Layout) -> bool {
+    // whether a resumed run can leave this year's output as it is
+
+    args.resume && output::progress(outfilename, timelattice, layout) == Some(timelattice.len())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn average_year(args: &RunArgs, inputs: &Inputs, grid: &Grid, provenance: &Provenance, layout: &Layout, storage: &Storage, baseline: Option<&Climatology>, outfilename: &Path, timelattice: &[Window], missing: &[NaiveDate]) -> Result<usize, netcdf::error::Error> {
+    // average one year of windows into outfilename, returning how many windows had to be computed
+
+    if complete(args, outfilename, timelattice, layout) {
+        return Ok(0);
     }
 
-    // tpa means
-    let mut meantpa = outfile.add_variable::<f64>("tpa_correction",&["time"])?;
-    for time in 0..timeidx{
-        if meanTPAcorrection[time] != -999.9 {
-            meantpa.put_values(&[meanTPAcorrection[time] / (TPAcorrectionCount[time] as f64)], time);
-        } else {
-            meantpa.put_values(&[meanTPAcorrection[time]], time);
+    // one window's worth of accumulators per requested variable, laid out like the upstream field;
+    // these are reused for every window so memory doesn't grow with the number of weeks
+    let path = timelattice.iter().flat_map(|w| w.dates()).find_map(|d| inputs.available(d)).ok_or("none of the daily inputs for this year could be found")?;
+    let f = netcdf::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    grid.check(&f, &path)?;
+    let mut accumulators = accumulators(args, &f, &path, baseline.is_some())?;
+    let mut slab: Vec<f64> = Vec::new();
+    // the climatology slot the last day fell in, kept until a day falls in another one
+    let names: Vec<String> = accumulators.iter().map(|acc| acc.name.clone()).collect();
+    let mut slot: Option<Slot> = None;
+
+    // work in a partial file that only takes the final name once every window is in,
+    // picking up where a previous attempt left off if asked to
+    let partial = output::partial_path(outfilename);
+    let resume_from = if args.resume {output::progress(&partial, timelattice, layout)} else {None};
+    let first = match resume_from {
+        Some(done) => done,
+        None => {
+            output::create(&partial, &f, grid, timelattice, &accumulators, missing, provenance, &output::streaming(storage))?;
+            0
+        }
+    };
+
+    for (timeidx, window) in timelattice.iter().enumerate().skip(first) {
+        accumulators.iter_mut().for_each(|acc| acc.reset());
+
+        // days that are missing are passed over, and too few days leaves the whole window as fill
+        let mut available: Vec<(NaiveDate, PathBuf)> = window.dates().into_iter().filter_map(|d| inputs.available(d).map(|p| (d, p))).collect();
+        if (available.len() as i64) < args.min_days {
+            available.clear();
+        }
+
+        // load upstream data for each day in the window, noting down where it came from
+        let mut sources = Vec::new();
+        for (date, path) in available.iter() {
+            let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| path.display().to_string());
+            sources.push(if args.checksums {format!("{} {}", name, checksum::crc32(path)?)} else {name});
+            if let Some(climatology) = baseline {
+                if !slot.as_ref().is_some_and(|s| s.holds(*date)) {
+                    slot = Some(climatology.load(*date, &names)?);
+                }
+            }
+            add_day(path, grid, &mut accumulators, &mut slab, slot.as_ref(), args.tpa_correction)?;
         }
-    }
 
-    // tpa counts
-    let mut tpa_nobs = outfile.add_variable::<f64>("tpa_correction_nobs",&["time"])?;  // track how many non-fill-value observations the mean is calculated over
-    for time in 0..timeidx{
-        tpa_nobs.put_values(&[TPAcorrectionCount[time]], time);
+        // write this window out before moving on to the next one
+        output::write_window(&partial, timeidx, &accumulators, &sources)?;
     }
 
-    // propagate dimensions
-    let dates = timewindow(timelattice[0], 7);
-    /// latitude
-    let f = netcdf::open(format!("data/dt_global_twosat_phy_l4_{}_vDT2021.nc", dates[3]))?;
-    let latitudes = &f.variable("latitude").expect("Could not find variable 'latitude'");
-    let mut lats = Vec::new();
-    for lat in 0..720 {
-        lats.push(latitudes.value::<f64, _>([lat])?);
+    // series chunks are only written once every window is in
+    if storage.chunking == Chunking::Series {
+        let rechunked = output::partial_path(&partial);
+        output::create(&rechunked, &f, grid, timelattice, &accumulators, missing, provenance, storage)?;
+        output::rechunk(&partial, &rechunked)?;
+        std::fs::rename(&rechunked, &partial).map_err(|e| format!("could not move {} to {}: {}", rechunked.display(), partial.display(), e))?;
     }
-    let mut latvals = outfile.add_variable::<f64>("latitude",&["latitude"])?;
-    latvals.put_values(&lats, (0));
-    /// longitudes
-    let longitudes = &f.variable("longitude").expect("Could not find variable 'longitude'");
-    let mut lons = Vec::new();
-    for lon in 0..1440 {
-        lons.push(longitudes.value::<f64, _>([lon])?);
+    std::fs::rename(&partial, outfilename).map_err(|e| format!("could not move {} to {}: {}", partial.display(), outfilename.display(), e))?;
+    Ok(timelattice.len() - first)
+}
+
+fn main() {
+    let cli = Cli::checked();
+    let result = match &cli.command {
+        Command::Average(args) => average(args),
+        Command::Inspect(args) => inspect(args),
+        Command::Validate(args) => validate(args),
+        Command::Climatology(args) => climatology(args),
+        Command::Trend(args) => trend(args),
+        Command::Gmsl(args) => gmsl(args)
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
     }
-    let mut lonvals = outfile.add_variable::<f64>("longitude",&["longitude"])?;
-    lonvals.put_values(&lons, (0));
-    /// timestamps
-    let mut timestamps = outfile.add_variable::<i64>("timestamps",&["time"])?;
-    timestamps.put_values(&timesteps, (0));
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_years_from_files() {
+        // only yearly files of the asked for stem count, however late the record runs
+        let dir = std::env::temp_dir().join(format!("ssh-record-years-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["sla_adt_mean_1993.nc", "sla_adt_mean_2023.nc", "sla_adt_mean_1994.nc.partial", "sla_adt_mean_trend_1990-2021.nc", "sla_adt_monthly_mean_1990.nc", "sla_adt_mean_gmsl_1990-2030.csv"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        assert_eq!(record_years(&dir, "sla_adt_mean").unwrap(), (1993, 2023));
+        assert_eq!(record_years(&dir, "sla_adt_monthly_mean").unwrap(), (1990, 1990));
+        assert!(record_years(&dir, "sla_adt_annual_mean").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
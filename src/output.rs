@@ -0,0 +1,787 @@
+use chrono::NaiveDate;
+use netcdf::AttrValue;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::accumulate::Accumulator;
+use crate::attributes::Packing;
+use crate::attributes;
+use crate::cli::Alignment;
+use crate::cli::Chunking;
+use crate::cli::FillValue;
+use crate::cli::Period;
+use crate::cli::Precision;
+use crate::cli::Statistic;
+use crate::derived;
+use crate::attributes::Number;
+use crate::grid::Grid;
+use crate::lattice;
+use crate::lattice::Window;
+
+// edge of the horizontal tiles used when chunking for time series access
+const TILE: usize = 32;
+
+// how the means and counts are laid out on disk
+pub struct Storage {
+    pub deflate: i32,
+    pub shuffle: bool,
+    pub chunking: Chunking,
+    pub precision: Precision
+}
+
+fn chunk_shape(chunking: Chunking, timesteps: usize, dims: &[(String, usize)]) -> Vec<usize> {
+    // one whole field per chunk for reading maps, or every window of a small tile for reading time series
+
+    let mut shape = Vec::new();
+    match chunking {
+        Chunking::Map => {
+            shape.push(1);
+            shape.extend(dims.iter().map(|(_, len)| *len));
+        },
+        Chunking::Series => {
+            shape.push(timesteps);
+            shape.extend(dims.iter().map(|(_, len)| TILE.min(*len)));
+        }
+    }
+    shape
+}
+
+pub fn store(var: &mut netcdf::VariableMut, storage: &Storage, chunks: &[usize]) -> Result<(), netcdf::error::Error> {
+    // apply the compression and chunking options to a freshly declared variable
+
+    var.chunking(chunks)?;
+    if storage.deflate > 0 {
+        var.compression(storage.deflate, storage.shuffle)?;
+    }
+    Ok(())
+}
+
+pub fn statistic_name(variable: &str, statistic: Statistic) -> String {
+    // output variable holding a statistic of an upstream variable
+
+    let suffix = match statistic {
+        Statistic::Variance => "var",
+        Statistic::Std => "std",
+        Statistic::Min => "min",
+        Statistic::Max => "max"
+    };
+    format!("{}_{}", variable, suffix)
+}
+
+pub fn streaming(storage: &Storage) -> Storage {
+    // how a file written one window at a time is laid out: series chunks span every window, so writing
+    // them window by window would re-read, re-deflate and rewrite each one every time; such files are
+    // streamed with map chunks and rechunked once they're complete
+
+    Storage{deflate: storage.deflate, shuffle: storage.shuffle, chunking: Chunking::Map, precision: storage.precision}
+}
+
+pub fn rechunk(from: &Path, to: &Path) -> Result<(), netcdf::error::Error> {
+    // copy every time dependent variable of a completed file into a freshly declared copy with different chunking,
+    // a band of TILE rows at a time, so each output chunk is written exactly once;
+    // every numeric type the outputs use round-trips through f64 exactly
+
+    let source = netcdf::open(from).map_err(|e| format!("{}: {}", from.display(), e))?;
+    let mut outfile = netcdf::append(to).map_err(|e| format!("{}: {}", to.display(), e))?;
+    for var in source.variables() {
+        let dims = var.dimensions();
+        if dims.first().map(|d| d.name()).as_deref() != Some("time") {
+            continue;
+        }
+        let name = var.name();
+        let mut target = outfile.variable_mut(&name).ok_or_else(|| format!("{}: '{}' was never declared", to.display(), name))?;
+        if var.vartype().is_string() {
+            for t in 0..dims[0].len() {
+                for d in 0..dims.get(1).map(|d| d.len()).unwrap_or(1) {
+                    let value = var.string_value([t, d])?;
+                    if !value.is_empty() {
+                        target.put_string(&value, [t, d])?;
+                    }
+                }
+            }
+            continue;
+        }
+        let rows = dims.get(1).map(|d| d.len()).unwrap_or(1);
+        for first in (0..rows).step_by(TILE) {
+            let mut extents = vec![netcdf::extent::Extent::from(..)];
+            if dims.len() > 1 {
+                extents.push(netcdf::extent::Extent::from(first..(first + TILE).min(rows)));
+                extents.extend((2..dims.len()).map(|_| netcdf::extent::Extent::from(..)));
+            }
+            let extents: netcdf::extent::Extents = extents.into();
+            let values = var.values::<f64, _>(extents.clone()).map_err(|e| format!("{}: reading '{}': {}", from.display(), name, e))?;
+            target.put_values(&values, extents)?;
+        }
+    }
+    if let Some(attr) = source.attribute("windows_completed") {
+        outfile.add_attribute("windows_completed", attr.value()?)?;
+    }
+    Ok(())
+}
+
+pub fn stem(period: Period, window: i64, stride: i64, alignment: Alignment, product: &str) -> String {
+    // start of the name of every yearly output of a kind of window and product, "mean" or "anomaly";
+    // anything but the forward weekly record says what its windows are, so runs can share an output directory
+
+    let mut stem = String::from("sla_adt");
+    match period {
+        Period::Days if window == 7 && stride == 7 => (),
+        Period::Days if stride == window => stem.push_str(&format!("_{}day", window)),
+        Period::Days => stem.push_str(&format!("_{}day_stride{}", window, stride)),
+        Period::Month => stem.push_str("_monthly"),
+        Period::Year => stem.push_str("_annual")
+    }
+    match alignment {
+        Alignment::Forward => (),
+        Alignment::Centered => stem.push_str("_centered"),
+        Alignment::Backward => stem.push_str("_backward")
+    }
+    format!("{}_{}", stem, product)
+}
+
+fn put_floats(var: &mut netcdf::VariableMut, values: &[f64], extents: netcdf::extent::Extents) -> Result<(), netcdf::error::Error> {
+    // write values into a variable declared as either f64 or f32
+
+    if var.vartype().is_f64() {
+        var.put_values(values, extents)
+    } else {
+        var.put_values(&values.iter().map(|v| *v as f32).collect::<Vec<f32>>(), extents)
+    }
+}
+
+// the fill value the weekly record has always used
+pub const SENTINEL: f64 = -999.9;
+
+pub fn fill_value(choice: FillValue, packing: &Packing) -> f64 {
+    // what to write for cells without a mean; upstream's packed fill falls back to the sentinel
+    // for variables that don't declare one
+
+    match choice {
+        FillValue::Sentinel => SENTINEL,
+        FillValue::Nan => f64::NAN,
+        FillValue::Upstream => packing.fill_value.or(packing.missing_value).unwrap_or(SENTINEL)
+    }
+}
+
+fn packed_fill(packing: &Packing) -> i32 {
+    // upstream's packed fill, as long as it's an int32
+
+    match packing.fill_value.or(packing.missing_value) {
+        Some(fill) if fill.fract() == 0.0 && fill >= i32::MIN as f64 && fill <= i32::MAX as f64 => fill as i32,
+        _ => i32::MIN
+    }
+}
+
+pub fn time_slice(timeidx: usize, dims: usize) -> netcdf::extent::Extents {
+    // everything at one time index of a variable with `dims` dimensions after time
+
+    let mut extents = vec![netcdf::extent::Extent::from(timeidx)];
+    extents.extend((0..dims).map(|_| netcdf::extent::Extent::from(..)));
+    extents.into()
+}
+
+pub fn partial_path(outfilename: &Path) -> PathBuf {
+    // where an output file is built up before it's complete
+
+    let mut name = outfilename.as_os_str().to_os_string();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+// attributes describing how upstream stored a field rather than what it is; the output is unpacked doubles
+const STORAGE_ATTRIBUTES: [&str; 6] = ["scale_factor", "add_offset", "missing_value", "valid_min", "valid_max", "grid_mapping"];
+
+// the variables the TOPEX-A drift correction applies to, and how outputs record whether it was
+pub const CORRECTED: [&str; 2] = ["sla", "adt"];
+pub const TPA_APPLIED: &str = "daily tpa_correction added to sla and adt before averaging";
+pub const TPA_NOT_APPLIED: &str = "not applied";
+
+// upstream global attributes worth keeping with the averages
+const GLOBAL_ATTRIBUTES: [&str; 3] = ["institution", "source", "references"];
+// global attributes of the averaged files worth keeping with the trends and series made from them
+pub const AVERAGED_ATTRIBUTES: [&str; 9] = ["institution", "source", "references", "product_version", "aggregation_period", "window_length_days", "window_stride_days", "window_alignment", "anomaly_baseline"];
+
+fn carry_attributes(from: &netcdf::Variable, to: &mut netcdf::VariableMut, leave: &[&str]) -> Result<(), netcdf::error::Error> {
+    // copy an upstream variable's descriptive attributes, leaving behind packing and library-internal ones
+    // as well as any named in leave
+
+    for attr in from.attributes() {
+        if attr.name().starts_with('_') || STORAGE_ATTRIBUTES.contains(&attr.name()) || leave.contains(&attr.name()) {
+            continue;
+        }
+        to.add_attribute(attr.name(), attr.value()?)?;
+    }
+    Ok(())
+}
+
+// how and from what an output file was made, stamped into its global attributes
+pub struct Provenance {
+    pub created: String,
+    pub command_line: String,
+    pub period: Period,
+    pub window: i64,
+    pub stride: i64,
+    pub alignment: Alignment,
+    pub product_version: String,
+    pub input_directory: String,
+    pub baseline: Option<String>,
+    pub tpa_correction: bool
+}
+
+pub fn stamp(outfile: &mut netcdf::MutableFile, source: &netcdf::File, carried: &[&str], created: &str, command_line: &str) -> Result<(), netcdf::error::Error> {
+    // the conventions, the named global attributes of the file it was made from, and when, how and by what it was made
+
+    outfile.add_attribute("Conventions", "CF-1.8")?;
+    for name in carried.iter() {
+        if let Some(attr) = source.attribute(name) {
+            outfile.add_attribute(name, attr.value()?)?;
+        }
+    }
+    outfile.add_attribute("date_created", created)?;
+    outfile.add_attribute("history", format!("{} {}", created, command_line))?;
+    outfile.add_attribute("tool_version", format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")))?;
+    outfile.add_attribute("command_line", command_line)?;
+    Ok(())
+}
+
+pub fn describe_run(outfile: &mut netcdf::MutableFile, upstream: &netcdf::File, provenance: &Provenance) -> Result<(), netcdf::error::Error> {
+    // global attributes every kind of output carries
+
+    stamp(outfile, upstream, &GLOBAL_ATTRIBUTES, &provenance.created, &provenance.command_line)?;
+    outfile.add_attribute("product_version", provenance.product_version.as_str())?;
+    outfile.add_attribute("input_directory", provenance.input_directory.as_str())?;
+    outfile.add_attribute("tpa_correction", if provenance.tpa_correction {TPA_APPLIED} else {TPA_NOT_APPLIED})?;
+    Ok(())
+}
+
+pub fn declare_grid(outfile: &mut netcdf::MutableFile, upstream: &netcdf::File, grid: &Grid) -> Result<(), netcdf::error::Error> {
+    // latitude and longitude dimensions and coordinates, described like upstream's
+
+    outfile.add_dimension("latitude", grid.latitude.len())?;
+    outfile.add_dimension("longitude", grid.longitude.len())?;
+
+    // latitude
+    let mut latvals = outfile.add_variable::<f64>("latitude",&["latitude"])?;
+    if let Some(var) = upstream.variable("latitude") {
+        carry_attributes(&var, &mut latvals, &[])?;
+    }
+    latvals.put_values(&grid.latitude, 0)?;
+    // longitudes
+    let mut lonvals = outfile.add_variable::<f64>("longitude",&["longitude"])?;
+    if let Some(var) = upstream.variable("longitude") {
+        carry_attributes(&var, &mut lonvals, &[])?;
+    }
+    lonvals.put_values(&grid.longitude, 0)?;
+    Ok(())
+}
+
+pub fn declare_accumulators(outfile: &mut netcdf::MutableFile, upstream: &netcdf::File, accumulators: &[Accumulator], longest: i64, cell_methods: &str, departures: bool, storage: &Storage) -> Result<(), netcdf::error::Error> {
+    // a mean, any other statistics and an observation count on the time dimension for each accumulator;
+    // longest is the most days any one count can reach, and departures marks means of departures from a climatology
+
+    for acc in accumulators.iter() {
+        for (dim, len) in acc.dims.iter() {
+            if outfile.dimension(dim).is_none() {
+                outfile.add_dimension(dim, *len)?;
+            }
+        }
+        let mut dims = vec!["time"];
+        dims.extend(acc.dims.iter().map(|(dim, _)| dim.as_str()));
+        let chunks = chunk_shape(storage.chunking, outfile.dimension("time").map(|d| d.len()).unwrap_or(1), &acc.dims);
+        // derived fields have no upstream variable of their own to describe them
+        let source = match acc.derived {
+            Some(_) => None,
+            None => Some(upstream.variable(&acc.name).ok_or_else(|| format!("could not find variable '{}'", acc.name))?)
+        };
+
+        // packed output reuses upstream's packing, so only applies to variables that were packed to begin with
+        let packed = match &source {
+            Some(source) if source.attribute("scale_factor").is_some() => Some(Packing::from_variable(source)?),
+            _ => None
+        };
+        let mut mean = match (storage.precision, &packed) {
+            (Precision::Packed, Some(packing)) => {
+                let mut mean = outfile.add_variable::<i32>(&acc.name, &dims)?;
+                store(&mut mean, storage, &chunks)?;
+                mean.set_fill_value(packed_fill(packing))?;
+                mean.add_attribute("scale_factor", packing.scale_factor)?;
+                mean.add_attribute("add_offset", packing.add_offset)?;
+                mean
+            },
+            (Precision::F64, _) => {
+                let mut mean = outfile.add_variable::<f64>(&acc.name, &dims)?;
+                store(&mut mean, storage, &chunks)?;
+                mean.set_fill_value(acc.fill)?;
+                mean
+            },
+            _ => {
+                let mut mean = outfile.add_variable::<f32>(&acc.name, &dims)?;
+                store(&mut mean, storage, &chunks)?;
+                mean.set_fill_value(acc.fill as f32)?;
+                mean
+            }
+        };
+        let described = acc.derived.map(derived::attributes).unwrap_or_default();
+        let text = |name: &str| match &source {
+            Some(source) => attributes::text(source.attribute(name)),
+            None => described.iter().find(|(n, _)| *n == name).map(|(_, value)| String::from(*value))
+        };
+        let long_name = text("long_name").unwrap_or_else(|| acc.name.clone());
+        // a departure is no longer the quantity upstream's standard name says it is
+        let long_name = if departures {format!("departure of {} from its climatology", long_name)} else {long_name};
+        let leave: &[&str] = if departures {&["standard_name", "long_name"]} else {&[]};
+        match &source {
+            Some(source) => carry_attributes(source, &mut mean, leave)?,
+            None => for (name, value) in described.iter().filter(|(name, _)| !leave.contains(name)) {
+                mean.add_attribute(name, *value)?;
+            }
+        }
+        if departures {
+            mean.add_attribute("long_name", long_name.as_str())?;
+        }
+        mean.add_attribute("cell_methods", cell_methods)?;
+        mean.add_attribute("ancillary_variables", acc.nobs.as_str())?;
+
+        // other statistics are never packed, since their ranges don't match upstream's
+        for statistic in acc.statistics.iter() {
+            let name = statistic_name(&acc.name, *statistic);
+            let mut var = if storage.precision == Precision::F64 {
+                let mut var = outfile.add_variable::<f64>(&name, &dims)?;
+                store(&mut var, storage, &chunks)?;
+                var.set_fill_value(acc.fill)?;
+                var
+            } else {
+                let mut var = outfile.add_variable::<f32>(&name, &dims)?;
+                store(&mut var, storage, &chunks)?;
+                var.set_fill_value(acc.fill as f32)?;
+                var
+            };
+            let (description, method) = match statistic {
+                Statistic::Variance => ("sample variance", "variance"),
+                Statistic::Std => ("sample standard deviation", "standard_deviation"),
+                Statistic::Min => ("minimum", "minimum"),
+                Statistic::Max => ("maximum", "maximum")
+            };
+            var.add_attribute("long_name", format!("{} of daily {}", description, long_name))?;
+            if let Some(units) = text("units") {
+                var.add_attribute("units", if *statistic == Statistic::Variance {format!("({})2", units)} else {units})?;
+            }
+            var.add_attribute("cell_methods", format!("time: {}", method))?;
+            var.add_attribute("ancillary_variables", acc.nobs.as_str())?;
+        }
+
+        // track how many non-fill-value observations the mean is calculated over, once per count variable;
+        // a window rarely has more days than fit in a byte, but years and climatologies do
+        if outfile.variable(&acc.nobs).is_some() {
+            continue;
+        }
+        let sharing: Vec<&str> = accumulators.iter().filter(|a| a.nobs == acc.nobs).map(|a| a.name.as_str()).collect();
+        let mut nobs = if longest <= u8::MAX as i64 {
+            let mut nobs = outfile.add_variable::<u8>(&acc.nobs, &dims)?;
+            nobs.add_attribute("valid_range", vec![0u8, longest as u8])?;
+            nobs
+        } else {
+            let mut nobs = outfile.add_variable::<i16>(&acc.nobs, &dims)?;
+            nobs.add_attribute("valid_range", vec![0i16, longest as i16])?;
+            nobs
+        };
+        store(&mut nobs, storage, &chunks)?;
+        nobs.add_attribute("standard_name", "number_of_observations")?;
+        nobs.add_attribute("long_name", format!("number of daily values averaged into {}", sharing.join(", ")))?;
+        nobs.add_attribute("units", "1")?;
+    }
+
+    Ok(())
+}
+
+// global attributes saying how an output's windows were laid out and what they're departures from
+const WINDOW_ATTRIBUTES: [&str; 5] = ["aggregation_period", "window_length_days", "window_stride_days", "window_alignment", "anomaly_baseline"];
+
+pub fn window_attributes(provenance: &Provenance) -> Vec<(&'static str, AttrValue)> {
+    // the values of whichever of the window attributes apply to a run
+
+    let mut attributes = match provenance.period {
+        Period::Days => vec![
+            ("aggregation_period", AttrValue::from("days")),
+            ("window_length_days", AttrValue::from(provenance.window as i32)),
+            ("window_stride_days", AttrValue::from(provenance.stride as i32))
+        ],
+        Period::Month => vec![("aggregation_period", AttrValue::from("calendar month"))],
+        Period::Year => vec![("aggregation_period", AttrValue::from("calendar year"))]
+    };
+    attributes.push(("window_alignment", AttrValue::from(match provenance.alignment {
+        Alignment::Forward => "forward",
+        Alignment::Centered => "centered",
+        Alignment::Backward => "backward"
+    })));
+    if let Some(baseline) = &provenance.baseline {
+        attributes.push(("anomaly_baseline", AttrValue::from(baseline.as_str())));
+    }
+    attributes
+}
+
+pub fn time_bounds(windows: &[Window], epoch: NaiveDate) -> Vec<f64> {
+    // each window runs from the start of its first day to the start of the day after its last, in days since epoch
+
+    windows.iter().flat_map(|w| {
+        let first = w.start.signed_duration_since(epoch).num_days();
+        [first as f64, (first + w.length) as f64]
+    }).collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create(path: &Path, upstream: &netcdf::File, grid: &Grid, windows: &[Window], accumulators: &[Accumulator], missing: &[NaiveDate], provenance: &Provenance, storage: &Storage) -> Result<(), netcdf::error::Error> {
+    // set up a new netcdf file to hold a period's averages, with no windows written yet
+
+    let epoch = lattice::parse_date(lattice::EPOCH)?;
+    let timesteps: Vec<i64> = windows.iter().map(|w| w.timestamp(epoch)).collect();
+    let longest = windows.iter().map(|w| w.length).max().unwrap_or(1);
+
+    let mut outfile = netcdf::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    outfile.add_dimension("time", timesteps.len())?;
+
+    outfile.add_attribute("title", "Means of Copernicus daily gridded sea level fields over consecutive windows")?;
+    describe_run(&mut outfile, upstream, provenance)?;
+    for (name, value) in window_attributes(provenance) {
+        outfile.add_attribute(name, value)?;
+    }
+
+    declare_grid(&mut outfile, upstream, grid)?;
+    // time, labelled by the first, central or last day of each window
+    let units = format!("days since {} 00:00:00", lattice::EPOCH);
+    let label = match provenance.alignment {
+        Alignment::Forward => "first day of the averaging window",
+        Alignment::Centered => "central day of the averaging window",
+        Alignment::Backward => "last day of the averaging window"
+    };
+    let mut time = outfile.add_variable::<f64>("time", &["time"])?;
+    time.add_attribute("standard_name", "time")?;
+    time.add_attribute("long_name", label)?;
+    time.add_attribute("units", units.as_str())?;
+    time.add_attribute("calendar", "standard")?;
+    time.add_attribute("axis", "T")?;
+    time.add_attribute("bounds", "time_bnds")?;
+    time.put_values(&timesteps.iter().map(|t| *t as f64).collect::<Vec<f64>>(), 0)?;
+    // each window runs from the start of its first day to the end of its last
+    outfile.add_dimension("nv", 2)?;
+    let mut bounds = outfile.add_variable::<f64>("time_bnds", &["time", "nv"])?;
+    bounds.put_values(&time_bounds(windows, epoch), ..)?;
+    // timestamps, the same integer days the db-population branch reads
+    let mut timestamps = outfile.add_variable::<i64>("timestamps",&["time"])?;
+    timestamps.add_attribute("long_name", label)?;
+    timestamps.add_attribute("units", units.as_str())?;
+    timestamps.put_values(&timesteps, 0)?;
+
+    // declare means and observation counts up front, so each window can be written as soon as it's done
+    declare_accumulators(&mut outfile, upstream, accumulators, longest, "time: mean", provenance.baseline.is_some(), storage)?;
+
+    // how many daily files went into each window, and which ones weren't there
+    let mut ndays = outfile.add_variable::<i32>("ndays", &["time"])?;
+    ndays.add_attribute("long_name", "number of daily files averaged into the window")?;
+    ndays.add_attribute("units", "1")?;
+    outfile.add_dimension("day", longest as usize)?;
+    let mut files = outfile.add_string_variable("input_files", &["time", "day"])?;
+    files.add_attribute("long_name", "daily files averaged into the window, relative to input_directory")?;
+    if !missing.is_empty() {
+        let dates: Vec<String> = missing.iter().map(|d| d.to_string()).collect();
+        outfile.add_attribute("missing_dates", dates.join(","))?;
+    }
+
+    outfile.add_attribute("windows_completed", 0)?;
+    Ok(())
+}
+
+pub fn put_accumulators(outfile: &mut netcdf::MutableFile, timeidx: usize, accumulators: &[Accumulator]) -> Result<(), netcdf::error::Error> {
+    // write the means, other statistics and observation counts of each accumulator into one time slice
+
+    for (i, acc) in accumulators.iter().enumerate() {
+        let mut var = outfile.variable_mut(&acc.name).ok_or_else(|| format!("output variable '{}' was never declared", acc.name))?;
+        let means = acc.means();
+        if var.vartype().is_f64() || var.vartype().is_f32() {
+            put_floats(&mut var, &means, time_slice(timeidx, acc.dims.len()))?;
+        } else {
+            // repack with the scale, offset and fill the variable was declared with
+            let packing = Packing::from_variable(&var)?;
+            let fill = packed_fill(&packing);
+            let packed: Vec<i32> = means.iter().zip(acc.count.iter()).map(|(m, c)| {
+                if *c > 0 {((m - packing.add_offset) / packing.scale_factor).round() as i32} else {fill}
+            }).collect();
+            var.put_values(&packed, time_slice(timeidx, acc.dims.len()))?;
+        }
+        for statistic in acc.statistics.iter() {
+            let name = statistic_name(&acc.name, *statistic);
+            let mut var = outfile.variable_mut(&name).ok_or_else(|| format!("output variable '{}' was never declared", name))?;
+            put_floats(&mut var, &acc.statistic(*statistic), time_slice(timeidx, acc.dims.len()))?;
+        }
+
+        // a shared count is written once, and only holds if every variable sharing it has the same mask
+        if let Some(first) = accumulators[..i].iter().find(|a| a.nobs == acc.nobs) {
+            if first.count != acc.count {
+                return Err(format!("'{}' and '{}' have different masks in window {}, so can't share '{}'; rerun without --shared-nobs", first.name, acc.name, timeidx, acc.nobs).into());
+            }
+            continue;
+        }
+        let mut var = outfile.variable_mut(&acc.nobs).ok_or_else(|| format!("output variable '{}' was never declared", acc.nobs))?;
+        if var.vartype().is_u8() {
+            var.put_values(&acc.count.iter().map(|c| *c as u8).collect::<Vec<u8>>(), time_slice(timeidx, acc.dims.len()))?;
+        } else {
+            var.put_values(&acc.count.iter().map(|c| *c as i16).collect::<Vec<i16>>(), time_slice(timeidx, acc.dims.len()))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn write_window(path: &Path, timeidx: usize, accumulators: &[Accumulator], inputs: &[String]) -> Result<(), netcdf::error::Error> {
+    // write the means, other statistics, observation counts and daily inputs of one window into their time slice and count it as done;
+    // the file is reopened for every window so that everything before a crash has made it to disk
+
+    let mut outfile = netcdf::append(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    put_accumulators(&mut outfile, timeidx, accumulators)?;
+    let mut var = outfile.variable_mut("ndays").ok_or("output variable 'ndays' was never declared")?;
+    var.put_values(&[inputs.len() as i32], timeidx)?;
+    let mut var = outfile.variable_mut("input_files").ok_or("output variable 'input_files' was never declared")?;
+    for (day, input) in inputs.iter().enumerate() {
+        var.put_string(input, [timeidx, day])?;
+    }
+    outfile.add_attribute("windows_completed", (timeidx + 1) as i32)?;
+
+    Ok(())
+}
+
+// coordinates and bookkeeping every averaged output declares besides its accumulators
+const FIXED_VARIABLES: [&str; 7] = ["latitude", "longitude", "time", "time_bnds", "timestamps", "ndays", "input_files"];
+
+// what an averaged output declares besides its windows: how they were laid out, what they're departures from,
+// whether they were drift corrected, and every variable, with the storage type and fill value of the means and
+// other statistics; an existing file is only picked up by a run that would have declared the same
+
+#[derive(Debug)]
+pub struct Layout {
+    pub attributes: Vec<(&'static str, Option<AttrValue>)>,
+    pub tpa_correction: bool,
+    pub variables: Vec<(String, Option<&'static str>, Option<f64>)>
+}
+
+fn storage_type(vartype: &netcdf::types::VariableType) -> Option<&'static str> {
+    if vartype.is_f64() {
+        Some("f64")
+    } else if vartype.is_f32() {
+        Some("f32")
+    } else if vartype.is_i32() {
+        Some("i32")
+    } else {
+        None
+    }
+}
+
+impl Layout {
+    pub fn new(provenance: &Provenance, accumulators: &[Accumulator], precision: Precision, packed: &[String]) -> Layout {
+        // what create and declare_accumulators set up for a run, with packed naming the upstream variables that come packed;
+        // counts are only checked by name, since their type depends on the longest window of each year
+
+        let written = window_attributes(provenance);
+        let attributes = WINDOW_ATTRIBUTES.iter().map(|name| (*name, written.iter().find(|(n, _)| n == name).map(|(_, value)| value.clone()))).collect();
+        let mut variables: Vec<(String, Option<&'static str>, Option<f64>)> = FIXED_VARIABLES.iter().map(|name| (String::from(*name), None, None)).collect();
+        let float = if precision == Precision::F64 {"f64"} else {"f32"};
+        let fill = |fill: f64| if precision == Precision::F64 {fill} else {fill as f32 as f64};
+        for acc in accumulators.iter() {
+            if precision == Precision::Packed && acc.derived.is_none() && packed.contains(&acc.name) {
+                variables.push((acc.name.clone(), Some("i32"), None));
+            } else {
+                variables.push((acc.name.clone(), Some(float), Some(fill(acc.fill))));
+            }
+            for statistic in acc.statistics.iter() {
+                variables.push((statistic_name(&acc.name, *statistic), Some(float), Some(fill(acc.fill))));
+            }
+            if !variables.iter().any(|(name, _, _)| *name == acc.nobs) {
+                variables.push((acc.nobs.clone(), None, None));
+            }
+        }
+        variables.sort_by(|a, b| a.0.cmp(&b.0));
+        Layout{attributes, tpa_correction: provenance.tpa_correction, variables}
+    }
+
+    pub fn read(f: &netcdf::File) -> Layout {
+        // the layout of an existing file
+
+        let attributes = WINDOW_ATTRIBUTES.iter().map(|name| (*name, f.attribute(name).and_then(|a| a.value().ok()))).collect();
+        let tpa_correction = attributes::text(f.attribute("tpa_correction")).is_some_and(|s| s == TPA_APPLIED);
+        let mut variables: Vec<(String, Option<&'static str>, Option<f64>)> = f.variables().map(|var| {
+            let fill = var.attribute("_FillValue").and_then(|a| a.value().ok()).and_then(|v| Number::try_from(v).ok()).map(|n| n.v);
+            (var.name(), storage_type(&var.vartype()), fill)
+        }).collect();
+        variables.sort_by(|a, b| a.0.cmp(&b.0));
+        Layout{attributes, tpa_correction, variables}
+    }
+
+    pub fn admits(&self, found: &Layout) -> bool {
+        // whether a layout read from an existing file is the one this run would declare
+
+        let same_fill = |wanted: &Option<f64>, found: &Option<f64>| match (wanted, found) {
+            (None, _) => true,
+            (Some(a), Some(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (Some(_), None) => false
+        };
+        self.attributes == found.attributes
+            && self.tpa_correction == found.tpa_correction
+            && self.variables.len() == found.variables.len()
+            && self.variables.iter().zip(found.variables.iter()).all(|((name, kind, fill), (n, k, f))| {
+                name == n && (kind.is_none() || kind == k) && same_fill(fill, f)
+            })
+    }
+}
+
+pub fn layout(upstream: &netcdf::File, provenance: &Provenance, accumulators: &[Accumulator], precision: Precision) -> Layout {
+    // the layout of this run's outputs, given a daily upstream file to tell which variables come packed
+
+    let packed: Vec<String> = accumulators.iter().filter(|acc| upstream.variable(&acc.name).is_some_and(|v| v.attribute("scale_factor").is_some())).map(|acc| acc.name.clone()).collect();
+    Layout::new(provenance, accumulators, precision, &packed)
+}
+
+pub fn progress(path: &Path, windows: &[Window], layout: &Layout) -> Option<usize> {
+    // how many leading windows of an existing output file are done, or None if it can't be picked up,
+    // e.g. because it's missing, unreadable, or was made for different windows, variables or storage
+
+    let epoch = lattice::parse_date(lattice::EPOCH).ok()?;
+    let timesteps: Vec<i64> = windows.iter().map(|w| w.timestamp(epoch)).collect();
+    let f = netcdf::open(path).ok()?;
+    if f.dimension("time")?.len() != timesteps.len() {
+        return None;
+    }
+    if f.variable("timestamps")?.values::<i64, _>(..).ok()? != timesteps {
+        return None;
+    }
+    if f.variable("time_bnds")?.values::<f64, _>(..).ok()? != time_bounds(windows, epoch) {
+        return None;
+    }
+    if !layout.admits(&Layout::read(&f)) {
+        return None;
+    }
+
+    let done = Number::try_from(f.attribute("windows_completed")?.value().ok()?).ok()?.v as usize;
+    if done <= timesteps.len() {
+        Some(done)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Summation;
+
+    fn weekly() -> Provenance {
+        Provenance{
+            created: String::from("2024-01-01T00:00:00Z"),
+            command_line: String::from("ssh average"),
+            period: Period::Days,
+            window: 7,
+            stride: 7,
+            alignment: Alignment::Forward,
+            product_version: String::from("DT2021"),
+            input_directory: String::from("data"),
+            baseline: None,
+            tpa_correction: false
+        }
+    }
+
+    fn sla(statistics: &[Statistic]) -> Vec<Accumulator> {
+        vec![Accumulator::new("sla", vec![(String::from("latitude"), 2), (String::from("longitude"), 3)], SENTINEL, statistics, Summation::Neumaier)]
+    }
+
+    fn layout(provenance: &Provenance, accumulators: &[Accumulator], precision: Precision) -> Layout {
+        Layout::new(provenance, accumulators, precision, &[String::from("sla")])
+    }
+
+    #[test]
+    fn same_run() {
+        let run = layout(&weekly(), &sla(&[]), Precision::F32);
+        assert!(run.admits(&layout(&weekly(), &sla(&[]), Precision::F32)));
+    }
+
+    #[test]
+    fn different_windows() {
+        let run = layout(&weekly(), &sla(&[]), Precision::F32);
+        assert!(!run.admits(&layout(&Provenance{stride: 1, ..weekly()}, &sla(&[]), Precision::F32)));
+        assert!(!run.admits(&layout(&Provenance{window: 8, ..weekly()}, &sla(&[]), Precision::F32)));
+        assert!(!run.admits(&layout(&Provenance{period: Period::Month, ..weekly()}, &sla(&[]), Precision::F32)));
+    }
+
+    #[test]
+    fn different_baseline_or_correction() {
+        let run = layout(&weekly(), &sla(&[]), Precision::F32);
+        let anomalies = Provenance{baseline: Some(String::from("monthly climatology over 1993-2012")), ..weekly()};
+        assert!(!run.admits(&layout(&anomalies, &sla(&[]), Precision::F32)));
+        assert!(!layout(&anomalies, &sla(&[]), Precision::F32).admits(&run));
+        assert!(!run.admits(&layout(&Provenance{tpa_correction: true, ..weekly()}, &sla(&[]), Precision::F32)));
+    }
+
+    #[test]
+    fn different_variables() {
+        let run = layout(&weekly(), &sla(&[]), Precision::F32);
+        assert!(!run.admits(&layout(&weekly(), &sla(&[Statistic::Std]), Precision::F32)));
+        assert!(!layout(&weekly(), &sla(&[Statistic::Std]), Precision::F32).admits(&run));
+    }
+
+    #[test]
+    fn different_storage() {
+        let run = layout(&weekly(), &sla(&[]), Precision::F32);
+        assert!(!run.admits(&layout(&weekly(), &sla(&[]), Precision::F64)));
+        assert!(!run.admits(&layout(&weekly(), &sla(&[]), Precision::Packed)));
+        let mut nan = sla(&[]);
+        nan[0].fill = f64::NAN;
+        assert!(!run.admits(&layout(&weekly(), &nan, Precision::F32)));
+        assert!(layout(&weekly(), &nan, Precision::F32).admits(&layout(&weekly(), &nan, Precision::F32)));
+    }
+
+    #[test]
+    fn different_alignment() {
+        // forward and centered weekly windows share their labels from 1994 on, so only the alignment and bounds tell them apart
+        let run = layout(&weekly(), &sla(&[]), Precision::F32);
+        assert!(!run.admits(&layout(&Provenance{alignment: Alignment::Centered, ..weekly()}, &sla(&[]), Precision::F32)));
+        assert!(!run.admits(&layout(&Provenance{alignment: Alignment::Backward, ..weekly()}, &sla(&[]), Precision::F32)));
+
+        let lattice = |alignment| lattice::Lattice::new(lattice::parse_date(lattice::ANCHOR).unwrap(), Period::Days, 7, 7, alignment, lattice::parse_date(lattice::RECORD_START).unwrap(), lattice::parse_date(lattice::RECORD_END).unwrap());
+        let epoch = lattice::parse_date(lattice::EPOCH).unwrap();
+        let forward = lattice(Alignment::Forward).year(1994);
+        let centered = lattice(Alignment::Centered).year(1994);
+        assert_eq!(forward.iter().map(|w| w.timestamp(epoch)).collect::<Vec<i64>>(), centered.iter().map(|w| w.timestamp(epoch)).collect::<Vec<i64>>());
+        assert_ne!(time_bounds(&forward, epoch), time_bounds(&centered, epoch));
+    }
+
+    #[test]
+    fn stems() {
+        // only the forward weekly record keeps the original names
+        assert_eq!(stem(Period::Days, 7, 7, Alignment::Forward, "mean"), "sla_adt_mean");
+        assert_eq!(stem(Period::Days, 7, 1, Alignment::Forward, "mean"), "sla_adt_7day_stride1_mean");
+        assert_eq!(stem(Period::Days, 7, 7, Alignment::Centered, "mean"), "sla_adt_centered_mean");
+        assert_eq!(stem(Period::Days, 10, 10, Alignment::Backward, "anomaly"), "sla_adt_10day_backward_anomaly");
+        assert_eq!(stem(Period::Month, 7, 7, Alignment::Forward, "mean"), "sla_adt_monthly_mean");
+        assert_eq!(stem(Period::Year, 7, 1, Alignment::Centered, "anomaly"), "sla_adt_annual_centered_anomaly");
+    }
+
+    #[test]
+    fn different_derived() {
+        // a run asking for derived fields can't pick up an output without them, or the other way round
+        let mut derived = sla(&[]);
+        let mut eke = Accumulator::new("eke", vec![(String::from("latitude"), 2), (String::from("longitude"), 3)], SENTINEL, &[], Summation::Neumaier);
+        eke.derived = Some(crate::cli::Derived::Eke);
+        derived.push(eke);
+        let run = layout(&weekly(), &sla(&[]), Precision::F32);
+        assert!(!run.admits(&layout(&weekly(), &derived, Precision::F32)));
+        assert!(!layout(&weekly(), &derived, Precision::F32).admits(&run));
+        assert!(layout(&weekly(), &derived, Precision::Packed).admits(&layout(&weekly(), &derived, Precision::Packed)));
+    }
+
+    #[test]
+    fn bounds() {
+        // from the start of a window's first day to the start of the day after its last
+        let epoch = lattice::parse_date(lattice::EPOCH).unwrap();
+        let windows = lattice::Lattice::new(lattice::parse_date(lattice::ANCHOR).unwrap(), Period::Days, 7, 7, Alignment::Centered, epoch, lattice::parse_date(lattice::RECORD_END).unwrap()).year(1993);
+        assert_eq!(time_bounds(&windows[..2], epoch), vec![6.0, 13.0, 13.0, 20.0]);
+        assert_eq!(windows[0].timestamp(epoch), 9);
+        let months = lattice::Lattice::new(epoch, Period::Month, 7, 7, Alignment::Forward, epoch, lattice::parse_date(lattice::RECORD_END).unwrap()).year(1993);
+        assert_eq!(time_bounds(&months[..3], epoch), vec![0.0, 31.0, 31.0, 59.0, 59.0, 90.0]);
+    }
+}
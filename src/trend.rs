@@ -0,0 +1,265 @@
+use chrono::Duration;
+use chrono::NaiveDate;
+use rayon::prelude::*;
+use std::path::Path;
+
+use crate::attributes::Packing;
+use crate::attributes;
+use crate::grid::Grid;
+use crate::output;
+use crate::output::Storage;
+
+// cells per unit of parallel work, as for the window accumulators
+const CHUNK: usize = 1 << 14;
+
+// trends are per Julian year
+pub const DAYS_PER_YEAR: f64 = 365.25;
+
+// per-cell sums of the least squares normal equations for one averaged variable over the whole record,
+// with time in years from the reference date and values in millimetres, laid out flat like the means;
+// each cell holds the sums of t, t^2, t^3, t^4, y, ty, t^2y and y^2 over the windows it has a mean in,
+// which is all a linear or quadratic fit and its residuals need, so the record is read only once
+
+pub struct Regression {
+    pub name: String,
+    pub nobs: String,
+    pub dims: Vec<(String, usize)>,
+    pub millimetres: f64,
+    pub count: Vec<i32>,
+    pub sums: Vec<[f64; 8]>
+}
+
+pub fn millimetres(units: &str) -> Option<f64> {
+    // factor taking a length in these units to millimetres
+
+    match units.trim() {
+        "m" | "meter" | "meters" | "metre" | "metres" => Some(1000.0),
+        "cm" | "centimeter" | "centimeters" | "centimetre" | "centimetres" => Some(10.0),
+        "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => Some(1.0),
+        _ => None
+    }
+}
+
+impl Regression {
+    pub fn new(name: &str, nobs: &str, dims: Vec<(String, usize)>, millimetres: f64) -> Regression {
+        let cells = dims.iter().map(|(_, len)| len).product();
+        Regression{
+            name: String::from(name),
+            nobs: String::from(nobs),
+            dims,
+            millimetres,
+            count: vec![0; cells],
+            sums: vec![[0.0; 8]; cells]
+        }
+    }
+
+    pub fn cells(&self) -> usize {
+        self.count.len()
+    }
+
+    pub fn add_field(&mut self, t: f64, packing: &Packing, field: &[f64], counts: &[i32]) {
+        // fold one window's means, taken at t years from the reference date, into the sums;
+        // a cell only counts if its window had observations and its mean isn't flagged as missing
+
+        let scale = self.millimetres;
+        self.sums.par_chunks_mut(CHUNK).zip(self.count.par_chunks_mut(CHUNK)).zip(field.par_chunks(CHUNK)).zip(counts.par_chunks(CHUNK)).for_each(|(((sums, count), field), counts)| {
+            for (((s, c), packed), nobs) in sums.iter_mut().zip(count.iter_mut()).zip(field.iter()).zip(counts.iter()) {
+                if *nobs <= 0 {
+                    continue;
+                }
+                if let Some(value) = packing.unpack(*packed) {
+                    let y = value * scale;
+                    s[0] += t;
+                    s[1] += t * t;
+                    s[2] += t * t * t;
+                    s[3] += t * t * t * t;
+                    s[4] += y;
+                    s[5] += t * y;
+                    s[6] += t * t * y;
+                    s[7] += y * y;
+                    *c += 1;
+                }
+            }
+        });
+    }
+
+    pub fn linear(&self, min_windows: i64) -> Vec<Option<(f64, f64)>> {
+        // per-cell slope of a straight line fit in mm/yr and its standard error,
+        // or None for cells with fewer than min_windows means or no spread in time
+
+        self.sums.par_iter().zip(self.count.par_iter()).map(|(s, c)| {
+            let n = *c as f64;
+            if (*c as i64) < min_windows.max(3) {
+                return None;
+            }
+            let det = n * s[1] - s[0] * s[0];
+            if det.is_nan() || det <= 0.0 {
+                return None;
+            }
+            let slope = (n * s[5] - s[0] * s[4]) / det;
+            let intercept = (s[4] - slope * s[0]) / n;
+            let rss = (s[7] - intercept * s[4] - slope * s[5]).max(0.0);
+            Some((slope, (rss / (n - 2.0) * n / det).sqrt()))
+        }).collect()
+    }
+
+    pub fn quadratic(&self, min_windows: i64) -> Vec<Option<(f64, f64)>> {
+        // per-cell acceleration, twice the quadratic coefficient of a parabola fit, in mm/yr^2 and its standard error
+
+        self.sums.par_iter().zip(self.count.par_iter()).map(|(s, c)| {
+            let n = *c as f64;
+            if (*c as i64) < min_windows.max(4) {
+                return None;
+            }
+            // the normal matrix [[n, t, t2], [t, t2, t3], [t2, t3, t4]] is symmetric, so its inverse is too
+            let m = [[n, s[0], s[1]], [s[0], s[1], s[2]], [s[1], s[2], s[3]]];
+            let cof = [
+                [m[1][1] * m[2][2] - m[1][2] * m[2][1], m[1][2] * m[2][0] - m[1][0] * m[2][2], m[1][0] * m[2][1] - m[1][1] * m[2][0]],
+                [m[0][2] * m[2][1] - m[0][1] * m[2][2], m[0][0] * m[2][2] - m[0][2] * m[2][0], m[0][1] * m[2][0] - m[0][0] * m[2][1]],
+                [m[0][1] * m[1][2] - m[0][2] * m[1][1], m[0][2] * m[1][0] - m[0][0] * m[1][2], m[0][0] * m[1][1] - m[0][1] * m[1][0]]
+            ];
+            let det = m[0][0] * cof[0][0] + m[0][1] * cof[0][1] + m[0][2] * cof[0][2];
+            if !det.is_finite() || det <= 0.0 {
+                return None;
+            }
+            let rhs = [s[4], s[5], s[6]];
+            let beta: Vec<f64> = (0..3).map(|i| (0..3).map(|j| cof[j][i] * rhs[j]).sum::<f64>() / det).collect();
+            let rss = (s[7] - beta[0] * rhs[0] - beta[1] * rhs[1] - beta[2] * rhs[2]).max(0.0);
+            let variance = rss / (n - 3.0) * cof[2][2] / det;
+            Some((2.0 * beta[2], 2.0 * variance.max(0.0).sqrt()))
+        }).collect()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn declare(outfile: &mut netcdf::MutableFile, name: &str, dims: &[&str], chunks: &[usize], storage: &Storage, long_name: String, units: &str, nobs: &str) -> Result<(), netcdf::error::Error> {
+    // one f32 map of fitted values
+
+    let mut var = outfile.add_variable::<f32>(name, dims)?;
+    output::store(&mut var, storage, chunks)?;
+    var.set_fill_value(output::SENTINEL as f32)?;
+    var.add_attribute("long_name", long_name)?;
+    var.add_attribute("units", units)?;
+    var.add_attribute("ancillary_variables", nobs)?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn write(path: &Path, source: &netcdf::File, grid: &Grid, regressions: &[Regression], quadratic: bool, min_windows: i64, reference: NaiveDate, inputs: &[String], created: &str, command_line: &str, storage: &Storage) -> Result<(), netcdf::error::Error> {
+    // fit every cell and write the maps of trends, their standard errors and how many windows went into them
+
+    let mut outfile = netcdf::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    output::stamp(&mut outfile, source, &output::AVERAGED_ATTRIBUTES, created, command_line)?;
+    outfile.add_attribute("title", "Per-cell trends of averaged Copernicus gridded sea level fields")?;
+    if let Some(attr) = source.attribute("tpa_correction") {
+        outfile.add_attribute("tpa_correction", attr.value()?)?;
+    }
+    outfile.add_attribute("trend_method", "ordinary least squares on the window means, unweighted and assuming independent residuals")?;
+    outfile.add_attribute("reference_date", reference.to_string())?;
+    outfile.add_attribute("min_windows", min_windows as i32)?;
+    outfile.add_attribute("input_files", inputs.join(","))?;
+
+    output::declare_grid(&mut outfile, source, grid)?;
+    for reg in regressions.iter() {
+        for (dim, len) in reg.dims.iter() {
+            if outfile.dimension(dim).is_none() {
+                outfile.add_dimension(dim, *len)?;
+            }
+        }
+        let dims: Vec<&str> = reg.dims.iter().map(|(dim, _)| dim.as_str()).collect();
+        let chunks: Vec<usize> = reg.dims.iter().map(|(_, len)| *len).collect();
+        let long_name = source.variable(&reg.name).and_then(|v| attributes::text(v.attribute("long_name"))).unwrap_or_else(|| reg.name.clone());
+        let nobs = format!("{}_trend_nobs", reg.name);
+
+        declare(&mut outfile, &format!("{}_trend", reg.name), &dims, &chunks, storage, format!("linear trend of {}", long_name), "mm/yr", &nobs)?;
+        declare(&mut outfile, &format!("{}_trend_stderr", reg.name), &dims, &chunks, storage, format!("standard error of the linear trend of {}", long_name), "mm/yr", &nobs)?;
+        if quadratic {
+            declare(&mut outfile, &format!("{}_acceleration", reg.name), &dims, &chunks, storage, format!("acceleration of {}, twice the quadratic coefficient of a parabola fit", long_name), "mm/yr2", &nobs)?;
+            declare(&mut outfile, &format!("{}_acceleration_stderr", reg.name), &dims, &chunks, storage, format!("standard error of the acceleration of {}", long_name), "mm/yr2", &nobs)?;
+        }
+        let mut var = outfile.add_variable::<i32>(&nobs, &dims)?;
+        output::store(&mut var, storage, &chunks)?;
+        var.add_attribute("standard_name", "number_of_observations")?;
+        var.add_attribute("long_name", format!("number of window means of {} the trend is fitted to", reg.name))?;
+        var.add_attribute("units", "1")?;
+
+        // fitted values, then their errors, with fill wherever there's no fit
+        let mut put = |name: String, fits: &[Option<(f64, f64)>]| -> Result<(), netcdf::error::Error> {
+            let values: Vec<f32> = fits.iter().map(|fit| fit.map(|(v, _)| v as f32).unwrap_or(output::SENTINEL as f32)).collect();
+            let errors: Vec<f32> = fits.iter().map(|fit| fit.map(|(_, e)| e as f32).unwrap_or(output::SENTINEL as f32)).collect();
+            outfile.variable_mut(&name).ok_or_else(|| format!("output variable '{}' was never declared", name))?.put_values(&values, ..)?;
+            outfile.variable_mut(&format!("{}_stderr", name)).ok_or_else(|| format!("output variable '{}_stderr' was never declared", name))?.put_values(&errors, ..)?;
+            Ok(())
+        };
+        put(format!("{}_trend", reg.name), &reg.linear(min_windows))?;
+        if quadratic {
+            put(format!("{}_acceleration", reg.name), &reg.quadratic(min_windows))?;
+        }
+        outfile.variable_mut(&nobs).ok_or_else(|| format!("output variable '{}' was never declared", nobs))?.put_values(&reg.count, ..)?;
+    }
+
+    Ok(())
+}
+
+pub fn reference_date(epoch: NaiveDate, first: f64, last: f64) -> NaiveDate {
+    // the day halfway through the record, which times are measured from to keep the fits well conditioned
+
+    epoch + Duration::days(((first + last) / 2.0).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fit(curve: impl Fn(f64) -> f64, noise: f64) -> Regression {
+        // weekly values in metres over twenty years either side of the reference date in one cell,
+        // and no observations at all in the other; the noise is deterministic but uncorrelated enough
+        let mut reg = Regression::new("sla", "sla_nobs", vec![(String::from("latitude"), 1), (String::from("longitude"), 2)], 1000.0);
+        for week in -520..=520 {
+            let t = week as f64 * 7.0 / DAYS_PER_YEAR;
+            let jitter = ((week as f64 * 12.9898).sin() * 43758.5453).fract();
+            reg.add_field(t, &Packing::physical(), &[curve(t) + noise * jitter, 0.0], &[1, 0]);
+        }
+        reg
+    }
+
+    #[test]
+    fn linear_exact() {
+        let fits = fit(|t| 0.1 + 0.0031 * t, 0.0).linear(10);
+        let (slope, stderr) = fits[0].unwrap();
+        assert!((slope - 3.1).abs() < 1e-9, "slope {}", slope);
+        assert!(stderr < 1e-6, "stderr {}", stderr);
+        assert_eq!(fits[1], None);
+    }
+
+    #[test]
+    fn linear_noisy() {
+        let (slope, stderr) = fit(|t| -0.05 + 0.0031 * t, 0.02).linear(10)[0].unwrap();
+        assert!(stderr > 0.01 && stderr < 0.1, "stderr {}", stderr);
+        assert!((slope - 3.1).abs() < 4.0 * stderr, "slope {} +- {}", slope, stderr);
+    }
+
+    #[test]
+    fn quadratic_exact() {
+        // an acceleration of 0.08 mm/yr^2 on top of a 3.1 mm/yr trend
+        let fits = fit(|t| 0.1 + 0.0031 * t + 0.00004 * t * t, 0.0).quadratic(10);
+        let (acceleration, stderr) = fits[0].unwrap();
+        assert!((acceleration - 0.08).abs() < 1e-9, "acceleration {}", acceleration);
+        assert!(stderr < 1e-6, "stderr {}", stderr);
+        assert_eq!(fits[1], None);
+    }
+
+    #[test]
+    fn quadratic_noisy() {
+        let (acceleration, stderr) = fit(|t| 0.1 + 0.0031 * t + 0.00004 * t * t, 0.02).quadratic(10)[0].unwrap();
+        assert!(stderr > 0.001 && stderr < 0.05, "stderr {}", stderr);
+        assert!((acceleration - 0.08).abs() < 4.0 * stderr, "acceleration {} +- {}", acceleration, stderr);
+    }
+
+    #[test]
+    fn too_few_windows() {
+        let reg = fit(|t| 0.0031 * t, 0.0);
+        assert_eq!(reg.linear(2000)[0], None);
+        assert_eq!(reg.quadratic(2000)[0], None);
+    }
+}